@@ -5,6 +5,10 @@ mod benches {
     extern crate game_of_life;
     extern crate test;
 
+    use self::game_of_life::automaton::Automaton;
+    use self::game_of_life::hashlife::HashLife;
+    use self::game_of_life::parsers::Pattern;
+    use self::game_of_life::sparse::SparseLife;
     use self::game_of_life::GameOfLife;
     use self::test::Bencher;
 
@@ -54,4 +58,46 @@ mod benches {
             gol.update();
         });
     }
+
+    #[bench]
+    fn bench_lib_update_larger_than_life(b: &mut Bencher) {
+        let mut gol = GameOfLife::new(WIDTH, HEIGHT);
+        gol.with_rule("R5,C0,M1,S34..58,B34..45,NM".parse().unwrap());
+        gol.init_randomly(CHANCE);
+
+        b.iter(|| {
+            gol.update();
+        });
+    }
+
+    /// Benchmark one generation of any engine.
+    fn bench_step<A: Automaton>(b: &mut Bencher, automaton: &mut A) {
+        b.iter(|| {
+            automaton.step();
+        });
+    }
+
+    #[bench]
+    fn bench_lib_step(b: &mut Bencher) {
+        let mut gol = GameOfLife::new(WIDTH, HEIGHT);
+        gol.init_with_file(&TEST_FILE).unwrap();
+
+        bench_step(b, &mut gol);
+    }
+
+    #[bench]
+    fn bench_sparse_step(b: &mut Bencher) {
+        let pattern = Pattern::from_file(TEST_FILE).unwrap();
+        let mut sparse_life = SparseLife::from_pattern(&pattern).unwrap();
+
+        bench_step(b, &mut sparse_life);
+    }
+
+    #[bench]
+    fn bench_hashlife_step(b: &mut Bencher) {
+        let pattern = Pattern::from_file(TEST_FILE).unwrap();
+        let mut hashlife = HashLife::from_pattern(&pattern).unwrap();
+
+        bench_step(b, &mut hashlife);
+    }
 }
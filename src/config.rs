@@ -1,9 +1,19 @@
-use super::clap::{App, Arg};
+use super::clap::{App, Arg, SubCommand};
+use game_of_life::image::{Shading, Style, Viewport};
+use game_of_life::rule::Rule;
+use game_of_life::topology::Topology;
+use game_of_life::Bounds;
+use run::{Run, StopCondition};
+use std::time::Duration;
 
 const ABOUT_MESSAGE: &str = "game-of-life emulates John Conway's game of life.
-Press Escape to exit, press C to toggle cursor capture and press Space or a mouse button to reinitialise grid.
-Pressing ctrl and scrolling will zoom in on the cells.
-A file can be dropped on the screen to load that file. When the grid is reinitialised, it will be reinitialised with that file.";
+Press Escape to exit, press C to toggle cursor capture and press Space or the left mouse button to reinitialise grid.
+Pressing ctrl and scrolling will zoom in on the cells under the cursor. Move the view with the arrow keys, WASD or by dragging with the right or middle mouse button. Press Z to zoom to fit the alive cells and 0 to reset the view.
+Press P to pause or resume, . to advance one generation, , to step back one generation and Home to go back to the oldest generation that is kept. Press + and - to change the speed.
+Press E to toggle editing: then drag with the left mouse button to draw or erase cells, starting with the opposite of the first cell, or with the right mouse button to erase cells.
+Press M to toggle selecting: then drag with the left mouse button to select a rectangle. Press Delete to clear, F to fill, N to randomise, R to rotate and H or V to flip the selection. Press ctrl-C, ctrl-X and ctrl-V to copy, cut and paste, and click to place the pasted cells. A file that is dropped while selecting is pasted.
+A file can be dropped on the screen to load that file. When the grid is reinitialised, it will be reinitialised with that file.
+With --tui, the board is drawn in the terminal. Press Q to exit, I and O to zoom in and out, B to switch between braille characters and half blocks and L to type the name of a file to load. Moving the view, Z, 0, Space and the keys that change the playback work like in the window.";
 
 pub struct Config {
     pub width: u32,
@@ -11,11 +21,25 @@ pub struct Config {
     pub cell_width: u32,
     pub chance: u8,
     pub fps: u64,
+    pub gps: u64,
     pub file: Option<String>,
     pub foreground: [f32; 4],
     pub background: [f32; 4],
     pub view_border: bool,
     pub full_screen: bool,
+    pub rule: Option<Rule>,
+    pub topology: Topology,
+    pub hex_cells: bool,
+    pub shading: Shading,
+    pub history: usize,
+    pub image: Option<String>,
+    pub gif: Option<String>,
+    pub frames: u64,
+    pub delay: u64,
+    pub region: Option<Bounds>,
+    pub tui: bool,
+    /// The options of the `run` subcommand, if it is used.
+    pub run: Option<Run>,
 }
 
 impl Config {
@@ -37,7 +61,7 @@ impl Config {
         .arg(Arg::with_name("cell-width")
             .short("c")
             .long("cell-width")
-            .help("Change width of a cell (in pixels).\nDefault: 10.")
+            .help("Change width of a cell (in pixels, or in dots of characters with --tui).\nDefault: 10, or 1 with --tui.")
             .takes_value(true))
         .arg(Arg::with_name("chance")
             .short("l")
@@ -46,13 +70,42 @@ impl Config {
             .takes_value(true))
         .arg(Arg::with_name("fps")
             .long("fps")
-            .help("The amount of updates and frames that should be performed per second.\nThis is the maximum frames per second; that is, the actual fps could be less.\nA frame rate of 0 fps will result in no fps limit.\nDefault: 24.")
+            .help("The amount of frames that should be rendered per second.\nThis is the maximum frames per second; that is, the actual fps could be less.\nA frame rate of 0 fps will result in no fps limit.\nDefault: 60.")
+            .takes_value(true))
+        .arg(Arg::with_name("gps")
+            .long("gps")
+            .help("The amount of generations that should be computed per second.\nThe generations are computed on a separate thread, so this is not limited by the frame rate.\nA rate of 0 gps will result in no gps limit.\nDefault: 24.")
+            .takes_value(true))
+        .arg(Arg::with_name("history")
+            .long("history")
+            .help("The number of previous generations that are kept to step back to, in the window or the terminal.\nEvery generation that is kept stores the cells that changed, which costs time and memory on large boards. Use 0 to keep no history.\nNo history is kept by the run subcommand, --image and --gif.\nDefault: 1000.")
             .takes_value(true))
         .arg(Arg::with_name("file")
             .short("f")
             .long("file")
             .help("The file that contains the board.\nIf this flag is passed, the board will be initialised with the board in the given file.\nDefault: None.")
             .takes_value(true))
+        .arg(Arg::with_name("image")
+            .long("image")
+            .help("Write an image of the board to the given file, instead of opening a window.\nThe image is written as PNG or PPM, depending on the extension of the file, with the colours, cell width, border and shading of the other options.\nDefault: None.")
+            .takes_value(true))
+        .arg(Arg::with_name("gif")
+            .long("gif")
+            .help("Record the board as an animated GIF in the given file, instead of opening a window.\nThe GIF is drawn with the colours, cell width, border and shading of the other options, and every state of a rule with more states has its own colour.\nDefault: None.")
+            .takes_value(true))
+        .arg(Arg::with_name("frames")
+            .long("frames")
+            .help("The number of generations that are recorded in a GIF.\nDefault: 100.")
+            .takes_value(true))
+        .arg(Arg::with_name("delay")
+            .long("delay")
+            .help("The time that every generation is shown in a GIF, in milliseconds.\nGIFs store this time in hundredths of a second, so it is rounded down to a multiple of 10 milliseconds.\nDefault: 100.")
+            .takes_value(true))
+        .arg(Arg::with_name("region")
+            .long("region")
+            .help("The region of the board that is written to an image or GIF, as the position of its top-left cell, its width and its height, example: 10,10,40,30.\nDefault: the whole board.")
+            .takes_value(true)
+            .validator(|s| parse_region(&s).map(|_| ())))
         .arg(Arg::with_name("foreground")
             .long("foreground")
             .help("Change the foreground colour of the cells.\nThe colour should be passed as a hexidecimal RGB colour, example: FFFFFF for white, 000000 for black.\nDefault: 000000.")
@@ -69,6 +122,48 @@ impl Config {
             .long("full-screen")
             .help("Launch in full-screen mode.")
             .takes_value(false))
+        .arg(Arg::with_name("rule")
+            .short("r")
+            .long("rule")
+            .help("The rule that is used to update the board.\nThe rule should be passed in B/S notation, example: B36/S23 for HighLife, or in S/B notation, example: 23/36.\nGenerations rules add the number of states, example: B2/S/C3 for Brian's Brain, or 345/2/4 for Star Wars.\nIsotropic non-totalistic rules are passed in Hensel notation, example: B3-cnqy/S23-a.\nRules ending in H or V use a hexagonal or von Neumann neighbourhood, example: B2/S34H.\nLarger than Life rules are passed in Golly's notation, example: R5,C0,M1,S34..58,B34..45,NM for Bosco's rule.\nRule tables are passed as the path of a Golly .rule file, or by the name of a built-in rule table, example: WireWorld.\nDefault: the rule declared by the file, or B3/S23.")
+            .takes_value(true)
+            .validator(|s| s.parse::<Rule>().map(|_| ())))
+        .arg(Arg::with_name("topology")
+            .short("t")
+            .long("topology")
+            .help("Change how the edges of the board are joined.\nThe topology should be one of plane, torus, klein-bottle, cross-surface or sphere.\nThe sphere topology only works on square boards.\nDefault: plane.")
+            .takes_value(true)
+            .validator(|s| s.parse::<Topology>().map(|_| ())))
+        .arg(Arg::with_name("tui")
+            .long("tui")
+            .help("Draw the board in the terminal with braille characters or half blocks, instead of opening a window.")
+            .takes_value(false))
+        .arg(Arg::with_name("hex-cells")
+            .long("hex-cells")
+            .help("Draw the cells on a skewed grid, so that patterns of rules with a hexagonal neighbourhood, like B2/S34H, look like they are on a hexagonal grid.")
+            .takes_value(false))
+        .arg(Arg::with_name("shading")
+            .long("shading")
+            .help("How a pixel is colored when zoomed out so far that several cells share it.\nThe shading should be one of density, which blends the foreground and background colours by the part of the cells that are alive, or any, which uses the foreground colour if any of the cells is alive.\nDefault: density.")
+            .takes_value(true)
+            .validator(|s| s.parse::<Shading>().map(|_| ())))
+        .subcommand(SubCommand::with_name("run")
+            .about("Compute generations of the board without opening a window, then write the final pattern and print the population and how long it took.\nThe board is initialised with the options that are passed before `run`, example: game-of-life -f examples/Pulsar.rle run -g 300 -o pulsar.rle.")
+            .arg(Arg::with_name("generations")
+                .short("g")
+                .long("generations")
+                .help("The number of generations that are computed.\nDefault: 100.")
+                .takes_value(true))
+            .arg(Arg::with_name("until")
+                .long("until")
+                .help("Stop before all generations have been computed, once a condition is met.\nThe condition should be extinct, which stops once no cells are alive, or periodic, which stops once the board is the same as in an earlier generation.\nDefault: None.")
+                .takes_value(true)
+                .validator(|s| s.parse::<StopCondition>().map(|_| ())))
+            .arg(Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .help("The file that the final pattern is written to, in the format that belongs to its extension: .rle, .cells, .life for Life 1.05 or .lif for Life 1.06.\nIf no file is given, the pattern is printed as RLE and the population and time are printed on the standard error.\nDefault: None.")
+                .takes_value(true)))
         .get_matches();
 
         macro_rules! parse_or_default {
@@ -85,24 +180,36 @@ impl Config {
 
         let width: u32 = parse_or_default!("width", 50);
         let height: u32 = parse_or_default!("height", 50);
-        let cell_width: u32 = parse_or_default!("cell-width", 10);
+        let tui: bool = matches.is_present("tui");
+        let run = matches.subcommand_matches("run").map(|matches| Run {
+            generations: matches
+                .value_of("generations")
+                .and_then(|s| s.trim().parse().ok())
+                .unwrap_or(100),
+            until: matches.value_of("until").and_then(|s| s.parse().ok()),
+            output: matches.value_of("output").map(String::from),
+        });
+        let cell_width: u32 = parse_or_default!("cell-width", if tui { 1 } else { 10 });
         let chance: u8 = parse_or_default!("chance", 15);
-        let fps: u64 = parse_or_default!("fps", 24);
-        let file: Option<String> = matches.value_of("file").and_then(|s| Some(String::from(s)));
+        let fps: u64 = parse_or_default!("fps", 60);
+        let gps: u64 = parse_or_default!("gps", 24);
+        let history: usize = parse_or_default!("history", 1000);
+        let file: Option<String> = matches.value_of("file").map(String::from);
+        let image: Option<String> = matches.value_of("image").map(String::from);
+        let gif: Option<String> = matches.value_of("gif").map(String::from);
+        let frames: u64 = parse_or_default!("frames", 100);
+        let delay: u64 = parse_or_default!("delay", 100);
+        let region: Option<Bounds> = matches
+            .value_of("region")
+            .and_then(|s| parse_region(s).ok());
 
         let foreground: u32 = matches
             .value_of("foreground")
-            .and_then(|s| match u32::from_str_radix(&s, 16) {
-                Ok(v) => Some(v),
-                Err(_) => None,
-            })
+            .and_then(|s| u32::from_str_radix(s, 16).ok())
             .unwrap_or(0x00_00_00);
         let background: u32 = matches
             .value_of("background")
-            .and_then(|s| match u32::from_str_radix(&s, 16) {
-                Ok(v) => Some(v),
-                Err(_) => None,
-            })
+            .and_then(|s| u32::from_str_radix(s, 16).ok())
             .unwrap_or(0xFF_FF_FF);
 
         let background = [
@@ -120,6 +227,16 @@ impl Config {
 
         let view_border: bool = matches.is_present("border");
         let full_screen: bool = matches.is_present("full-screen");
+        let hex_cells: bool = matches.is_present("hex-cells");
+        let rule: Option<Rule> = matches.value_of("rule").and_then(|s| s.parse().ok());
+        let shading: Shading = matches
+            .value_of("shading")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_default();
+        let topology: Topology = matches
+            .value_of("topology")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_default();
 
         Config {
             width,
@@ -127,11 +244,74 @@ impl Config {
             cell_width,
             chance,
             fps,
+            gps,
             file,
             foreground,
             background,
             view_border,
             full_screen,
+            rule,
+            topology,
+            hex_cells,
+            shading,
+            history,
+            image,
+            gif,
+            frames,
+            delay,
+            region,
+            tui,
+            run,
+        }
+    }
+
+    /// The time between two frames, as given by the frame rate. A frame rate of 0 has no delay.
+    pub fn frame_delay(&self) -> Duration {
+        if self.fps == 0 {
+            Duration::from_millis(0)
+        } else {
+            Duration::from_millis(((1.0 / self.fps as f32) * 1e3) as u64)
+        }
+    }
+
+    /// The colours, border and shading that the cells are drawn with.
+    pub fn style(&self) -> Style {
+        Style {
+            foreground: self.foreground,
+            background: self.background,
+            shading: self.shading,
+            border: self.view_border,
+        }
+    }
+
+    /// The region of the board and the cell width that images and GIFs are drawn with.
+    pub fn viewport(&self) -> Viewport {
+        let region = self
+            .region
+            .unwrap_or(((0, 0), (self.width as isize - 1, self.height as isize - 1)));
+
+        Viewport::new(region, self.cell_width as f32)
+    }
+}
+
+/// Parse a region that is given as the position of its top-left cell, its width and its height.
+fn parse_region(s: &str) -> Result<Bounds, String> {
+    let error = || {
+        format!(
+            "Invalid region `{}`, expected x,y,width,height with a positive width and height.",
+            s
+        )
+    };
+    let numbers = s
+        .split(',')
+        .map(|n| n.trim().parse::<isize>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| error())?;
+
+    match numbers[..] {
+        [x, y, width, height] if width > 0 && height > 0 => {
+            Ok(((x, y), (x + width - 1, y + height - 1)))
         }
+        _ => Err(error()),
     }
 }
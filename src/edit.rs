@@ -0,0 +1,79 @@
+use game_of_life::automaton::Automaton;
+use game_of_life::CellState;
+
+/// A stroke of the mouse that sets every cell it passes to the same state.
+struct Stroke {
+    state: CellState,
+    last: (isize, isize),
+}
+
+/// Edits cells with the mouse: a stroke that starts on a dead cell draws alive cells, a stroke
+/// that starts on any other cell erases cells, so a click toggles one cell.
+#[derive(Default)]
+pub struct Editor {
+    pub enabled: bool,
+    stroke: Option<Stroke>,
+}
+
+impl Editor {
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+        self.stroke = None;
+    }
+
+    /// Start a stroke at the given cell. If `erase` is true the stroke always erases cells.
+    pub fn begin(&mut self, automaton: &mut dyn Automaton, cell: (isize, isize), erase: bool) {
+        let state = if erase || automaton.get(cell.0, cell.1) != CellState::Dead {
+            CellState::Dead
+        } else {
+            CellState::Alive
+        };
+
+        automaton.set(cell.0, cell.1, state);
+        self.stroke = Some(Stroke { state, last: cell });
+    }
+
+    /// Continue the stroke to the given cell, setting all cells on the line from the previous
+    /// cell, so that fast strokes do not leave gaps.
+    pub fn extend(&mut self, automaton: &mut dyn Automaton, cell: (isize, isize)) {
+        if let Some(ref mut stroke) = self.stroke {
+            for (x, y) in line(stroke.last, cell) {
+                automaton.set(x, y, stroke.state);
+            }
+            stroke.last = cell;
+        }
+    }
+
+    pub fn end(&mut self) {
+        self.stroke = None;
+    }
+
+    /// Whether a stroke is being drawn.
+    pub fn is_drawing(&self) -> bool {
+        self.stroke.is_some()
+    }
+}
+
+/// The cells on the line between two cells, including both, using Bresenham's algorithm.
+pub fn line(from: (isize, isize), to: (isize, isize)) -> Vec<(isize, isize)> {
+    let (dx, dy) = ((to.0 - from.0).abs(), -(to.1 - from.1).abs());
+    let (step_x, step_y) = ((to.0 - from.0).signum(), (to.1 - from.1).signum());
+    let mut error = dx + dy;
+    let (mut x, mut y) = from;
+
+    let mut cells = vec![(x, y)];
+    while (x, y) != to {
+        let double_error = 2 * error;
+        if double_error >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if double_error <= dx {
+            error += dx;
+            y += step_y;
+        }
+        cells.push((x, y));
+    }
+
+    cells
+}
@@ -12,9 +12,21 @@ use std::ptr;
 const VERTEX_SHADER_SOURCE: &[u8] = include_bytes!("./shaders/cell.vs");
 const FRAGMENT_SHADER_SOURCE: &[u8] = include_bytes!("./shaders/cell.fs");
 
+/// A square that is drawn as one instance of the square of the vertex array.
+#[repr(C)]
+pub struct Instance {
+    /// The top-left corner, width and height of the square, on the OpenGL field with the y-axis
+    /// pointing down.
+    pub square: [f32; 4],
+    pub color: [f32; 4],
+}
+
 pub struct GraphicsContext {
     shader_program: GLuint,
     vao: GLuint,
+    /// The buffer that the instances are uploaded to.
+    instance_vbo: GLuint,
+    projection_location: GLint,
 }
 
 impl GraphicsContext {
@@ -22,6 +34,8 @@ impl GraphicsContext {
         GraphicsContext {
             shader_program: 0,
             vao: 0,
+            instance_vbo: 0,
+            projection_location: -1,
         }
     }
 
@@ -152,9 +166,36 @@ impl GraphicsContext {
             );
             gl::EnableVertexAttribArray(0);
 
+            // Every instance has its own square and color.
+            gl::GenBuffers(1, &mut self.instance_vbo);
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.instance_vbo);
+            let stride = mem::size_of::<Instance>() as GLsizei;
+            gl::VertexAttribPointer(1, 4, gl::FLOAT, gl::FALSE, stride, ptr::null());
+            gl::EnableVertexAttribArray(1);
+            gl::VertexAttribDivisor(1, 1);
+            gl::VertexAttribPointer(
+                2,
+                4,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                (4 * mem::size_of::<GLfloat>()) as *const c_void,
+            );
+            gl::EnableVertexAttribArray(2);
+            gl::VertexAttribDivisor(2, 1);
+
             gl::BindBuffer(gl::ARRAY_BUFFER, 0);
 
             gl::BindVertexArray(0);
+
+            // Look up the uniform once. The projection flips the y-axis, because OpenGL's y-axis
+            // is opposite to the one used by game of life.
+            let projection_str = CString::new("projection").unwrap();
+            self.projection_location =
+                gl::GetUniformLocation(self.shader_program, projection_str.as_ptr());
+            let projection = Matrix4::from_nonuniform_scale(1.0, -1.0, 1.0);
+            gl::UseProgram(self.shader_program);
+            gl::UniformMatrix4fv(self.projection_location, 1, gl::FALSE, projection.as_ptr());
         }
 
         Ok(())
@@ -167,29 +208,32 @@ impl GraphicsContext {
         }
     }
 
-    pub fn draw_square_with_scale_translation_color(
-        &self,
-        scale: Matrix4<f32>,
-        translate: Matrix4<f32>,
-        color: [f32; 4],
-    ) {
+    /// Draw all squares with a single draw call.
+    pub fn draw_squares(&self, instances: &[Instance]) {
+        if instances.is_empty() {
+            return;
+        }
+
         unsafe {
             gl::UseProgram(self.shader_program);
 
-            let scale_str = CString::new("scale").unwrap();
-            let translate_str = CString::new("translate").unwrap();
-            let color_str = CString::new("color").unwrap();
-
-            let scale_square = gl::GetUniformLocation(self.shader_program, scale_str.as_ptr());
-            gl::UniformMatrix4fv(scale_square, 1, gl::FALSE, scale.as_ptr());
-            let translate_square =
-                gl::GetUniformLocation(self.shader_program, translate_str.as_ptr());
-            gl::UniformMatrix4fv(translate_square, 1, gl::FALSE, translate.as_ptr());
-            let color_square = gl::GetUniformLocation(self.shader_program, color_str.as_ptr());
-            gl::Uniform4f(color_square, color[0], color[1], color[2], color[3]);
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.instance_vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                mem::size_of_val(instances) as GLsizeiptr,
+                instances.as_ptr() as *const c_void,
+                gl::STREAM_DRAW,
+            );
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
 
             gl::BindVertexArray(self.vao);
-            gl::DrawElements(gl::TRIANGLES, 6, gl::UNSIGNED_INT, ptr::null());
+            gl::DrawElementsInstanced(
+                gl::TRIANGLES,
+                6,
+                gl::UNSIGNED_INT,
+                ptr::null(),
+                instances.len() as GLsizei,
+            );
         }
     }
 }
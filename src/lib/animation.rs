@@ -0,0 +1,105 @@
+use super::automaton::Automaton;
+use super::gif::{self, SetParameter};
+use super::image::{self, Image, Style, Viewport};
+use std::collections::HashMap;
+use std::io::Write;
+use std::time::Duration;
+
+/// An animated GIF that loops forever, to which images of the same size are added as frames.
+/// The end of the GIF is written when the animation is dropped.
+pub struct Animation<W: Write> {
+    encoder: gif::Encoder<W>,
+    width: u16,
+    height: u16,
+    /// The time that every frame is shown, in hundredths of a second.
+    delay: u16,
+}
+
+impl<W: Write> Animation<W> {
+    /// Start an animation of frames of the given size, that are each shown for `delay`.
+    pub fn new(writer: W, width: usize, height: usize, delay: Duration) -> Result<Self, String> {
+        let max = usize::from(u16::MAX);
+        if width > max || height > max {
+            return Err(format!(
+                "An animation of {} by {} pixels is too large, at most {} by {} pixels are supported.",
+                width, height, max, max
+            ));
+        }
+        let (width, height) = (width as u16, height as u16);
+
+        let mut encoder =
+            gif::Encoder::new(writer, width, height, &[]).map_err(|e| e.to_string())?;
+        encoder
+            .set(gif::Repeat::Infinite)
+            .map_err(|e| e.to_string())?;
+        let delay = (delay.as_millis() / 10).min(u128::from(u16::MAX)) as u16;
+
+        Ok(Animation {
+            encoder,
+            width,
+            height,
+            delay,
+        })
+    }
+
+    /// Add an image as the next frame.
+    pub fn push(&mut self, image: &Image) -> Result<(), String> {
+        if image.width != usize::from(self.width) || image.height != usize::from(self.height) {
+            return Err(format!(
+                "A frame of {} by {} pixels does not fit in an animation of {} by {} pixels.",
+                image.width, image.height, self.width, self.height
+            ));
+        }
+
+        let mut frame = frame(image);
+        frame.delay = self.delay;
+        self.encoder.write_frame(&frame).map_err(|e| e.to_string())
+    }
+}
+
+/// Record a number of generations of the viewport of an automaton as an animated GIF, starting with
+/// the current generation. The automaton is advanced by one generation after every frame but the
+/// last one.
+pub fn record<W: Write>(
+    automaton: &mut dyn Automaton,
+    viewport: &Viewport,
+    style: &Style,
+    frames: u64,
+    delay: Duration,
+    writer: W,
+) -> Result<(), String> {
+    let first = image::render(automaton, viewport, style);
+    let mut animation = Animation::new(writer, first.width, first.height, delay)?;
+    animation.push(&first)?;
+
+    for _ in 1..frames {
+        automaton.step();
+        animation.push(&image::render(automaton, viewport, style))?;
+    }
+
+    Ok(())
+}
+
+/// The frame of an image, with a palette of the colors in it. Images with more than 256 colors,
+/// which only happens for rules with many states, are drawn with the 256 colors that approximate
+/// them best.
+fn frame(image: &Image) -> gif::Frame<'static> {
+    let (width, height) = (image.width as u16, image.height as u16);
+    let mut palette = Vec::new();
+    let mut indices = HashMap::new();
+    let mut pixels = Vec::with_capacity(image.pixels.len());
+
+    for pixel in &image.pixels {
+        let next = indices.len();
+        let index = *indices.entry(*pixel).or_insert_with(|| {
+            palette.extend_from_slice(pixel);
+            next
+        });
+        if index > usize::from(u8::MAX) {
+            return gif::Frame::from_rgb_speed(width, height, &image.bytes(), 10);
+        }
+        pixels.push(index as u8);
+    }
+
+    gif::Frame::from_palette_pixels(width, height, &pixels, &palette, None)
+}
@@ -0,0 +1,96 @@
+use super::{Bounds, CellState};
+
+/// A cellular automaton on a grid of cells, addressed by `(x, y)` positions like the cells of a
+/// `parsers::Pattern`.
+/// This is implemented by every engine, so that code that displays or analyses a universe does not
+/// depend on how it is stored or advanced.
+pub trait Automaton {
+    /// Advance the universe by one generation.
+    fn step(&mut self);
+
+    /// Advance the universe by the given number of generations.
+    fn step_n(&mut self, generations: u64) {
+        for _ in 0..generations {
+            self.step();
+        }
+    }
+
+    /// The number of generations that the universe has been advanced.
+    fn generation(&self) -> u64;
+
+    /// Get the state of the cell at the given position.
+    fn get(&self, x: isize, y: isize) -> CellState;
+
+    /// Set the state of the cell at the given position.
+    fn set(&mut self, x: isize, y: isize, state: CellState);
+
+    /// The number of states a cell can be in: 2 for alive and dead, more if the rule has dying
+    /// states.
+    fn states(&self) -> u8 {
+        2
+    }
+
+    /// The colour of a state, as red, green and blue components, if the rule declares one.
+    fn state_color(&self, _state: u8) -> Option<[u8; 3]> {
+        None
+    }
+
+    /// The number of alive cells.
+    fn population(&self) -> u64;
+
+    /// The smallest rectangle that contains all alive cells, as the positions of its top-left and
+    /// bottom-right cells. Returns `None` if there are no alive cells.
+    fn bounds(&self) -> Option<Bounds>;
+
+    /// Call `f` with the position of every alive cell in the region, which includes both of its
+    /// corners.
+    fn for_each_alive_cell(&self, region: Bounds, f: &mut dyn FnMut(isize, isize)) {
+        let ((min_x, min_y), (max_x, max_y)) = region;
+        for y in min_y..max_y + 1 {
+            for x in min_x..max_x + 1 {
+                if self.get(x, y) == CellState::Alive {
+                    f(x, y);
+                }
+            }
+        }
+    }
+
+    /// Call `f` with the position and state of every cell in the region that is not dead.
+    /// Engines that have dying states override this, by default only alive cells are visited.
+    fn for_each_cell(&self, region: Bounds, f: &mut dyn FnMut(isize, isize, CellState)) {
+        self.for_each_alive_cell(region, &mut |x, y| f(x, y, CellState::Alive));
+    }
+
+    /// The number of alive cells in each square block of `block` by `block` cells of the region,
+    /// row after row, starting at the top-left corner of the region. The blocks at the right and
+    /// bottom edges are cut off by the region. See `blocks` for the number of blocks.
+    fn block_populations(&self, region: Bounds, block: usize) -> Vec<u32> {
+        let ((min_x, min_y), _) = region;
+        let (columns, rows) = blocks(region, block);
+        let mut populations = vec![0; columns * rows];
+        self.for_each_alive_cell(region, &mut |x, y| {
+            let (column, row) = ((x - min_x) as usize / block, (y - min_y) as usize / block);
+            populations[row * columns + column] += 1;
+        });
+
+        populations
+    }
+
+    /// The positions of all alive cells in the region, which includes both of its corners.
+    fn alive_cells(&self, region: Bounds) -> Vec<(isize, isize)> {
+        let mut cells = Vec::new();
+        self.for_each_alive_cell(region, &mut |x, y| cells.push((x, y)));
+
+        cells
+    }
+}
+
+/// The number of columns and rows of square blocks of `block` by `block` cells that cover the
+/// region.
+pub fn blocks(region: Bounds, block: usize) -> (usize, usize) {
+    let ((min_x, min_y), (max_x, max_y)) = region;
+    let width = (max_x - min_x + 1).max(0) as usize;
+    let height = (max_y - min_y + 1).max(0) as usize;
+
+    (width.div_ceil(block), height.div_ceil(block))
+}
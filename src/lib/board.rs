@@ -0,0 +1,366 @@
+use super::isotropic::Isotropic;
+use super::rule::Rule;
+use super::CellState;
+
+/// The number of cells that are stored in one word.
+const WORD_BITS: usize = 64;
+
+/// A board that stores one bit per cell.
+/// The cells are stored in rows of `u64` words, with the rows stored after each other. The bits
+/// of a row's last word that lie outside of the board are always zero.
+///
+/// Boards of rules with more than two states also store the number of the state of every cell,
+/// in `states`. The bits then still hold which cells are alive.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Board {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+    /// The state of every cell as given by `u8::from(CellState)`, row after row. Empty if the
+    /// board only stores alive and dead cells.
+    states: Vec<u8>,
+}
+
+impl Board {
+    /// Return a new board with only dead cells.
+    pub fn new(width: usize, height: usize) -> Board {
+        let words_per_row = width.div_ceil(WORD_BITS);
+
+        Board {
+            width,
+            height,
+            words_per_row,
+            words: vec![0; words_per_row * height],
+            states: Vec::new(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The number of words that are used to store one row.
+    pub fn words_per_row(&self) -> usize {
+        self.words_per_row
+    }
+
+    /// Get the state of the cell at the given position.
+    /// Panics if the position lies outside of the board.
+    pub fn get(&self, x: usize, y: usize) -> CellState {
+        assert!(x < self.width && y < self.height);
+
+        if !self.states.is_empty() {
+            return self.states[y * self.width + x].into();
+        }
+
+        let word = self.words[y * self.words_per_row + x / WORD_BITS];
+        (word >> (x % WORD_BITS) & 1 == 1).into()
+    }
+
+    /// Set the state of the cell at the given position.
+    /// Panics if the position lies outside of the board.
+    /// Setting a dying cell makes the board store the state of every cell.
+    pub fn set(&mut self, x: usize, y: usize, state: CellState) {
+        assert!(x < self.width && y < self.height);
+
+        if let CellState::Dying(_) = state {
+            self.set_multi_state(true);
+        }
+        if !self.states.is_empty() {
+            self.states[y * self.width + x] = state.into();
+        }
+
+        let word = &mut self.words[y * self.words_per_row + x / WORD_BITS];
+        let mask = 1 << (x % WORD_BITS);
+        if state == CellState::Alive {
+            *word |= mask;
+        } else {
+            *word &= !mask;
+        }
+    }
+
+    /// Kill all cells.
+    pub fn clear(&mut self) {
+        for word in &mut self.words {
+            *word = 0;
+        }
+        for state in &mut self.states {
+            *state = 0;
+        }
+    }
+
+    /// Whether the board stores the state of every cell, instead of only which cells are alive.
+    pub fn is_multi_state(&self) -> bool {
+        !self.states.is_empty()
+    }
+
+    /// Start or stop storing the state of every cell. When the states are no longer stored, dying
+    /// cells become dead.
+    pub fn set_multi_state(&mut self, multi_state: bool) {
+        if multi_state == self.is_multi_state() || self.width * self.height == 0 {
+            return;
+        }
+
+        self.states = if multi_state {
+            (0..self.width * self.height)
+                .map(|i| {
+                    let (x, y) = (i % self.width, i / self.width);
+                    (self.row(y)[x / WORD_BITS] >> (x % WORD_BITS) & 1) as u8
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+    }
+
+    /// The states of the cells of a row, or an empty slice if the board only stores which cells
+    /// are alive.
+    pub fn states_row(&self, y: usize) -> &[u8] {
+        if self.states.is_empty() {
+            &[]
+        } else {
+            &self.states[y * self.width..(y + 1) * self.width]
+        }
+    }
+
+    /// The words of a row. Bit `x % 64` of word `x / 64` is set if the cell at `x` is alive.
+    pub fn row(&self, y: usize) -> &[u64] {
+        &self.words[y * self.words_per_row..(y + 1) * self.words_per_row]
+    }
+
+    /// The number of alive cells.
+    pub fn population(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Set the state of every cell, from the numbers of the states of all cells, row after row.
+    pub(crate) fn set_states(&mut self, states: &[u8]) {
+        assert_eq!(states.len(), self.width * self.height);
+
+        for word in &mut self.words {
+            *word = 0;
+        }
+        for (i, _) in states.iter().enumerate().filter(|&(_, &state)| state == 1) {
+            let (x, y) = (i % self.width, i / self.width);
+            self.words[y * self.words_per_row + x / WORD_BITS] |= 1 << (x % WORD_BITS);
+        }
+        if !self.states.is_empty() {
+            self.states.copy_from_slice(states);
+        }
+    }
+
+    pub(crate) fn words_and_states(&self) -> (&[u64], &[u8]) {
+        (&self.words, &self.states)
+    }
+
+    pub(crate) fn words_and_states_mut(&mut self) -> (&mut [u64], &mut [u8]) {
+        (&mut self.words, &mut self.states)
+    }
+
+    /// The mask of the bits of a row's last word that lie inside of the board.
+    pub(crate) fn last_word_mask(&self) -> u64 {
+        match self.width % WORD_BITS {
+            0 => !0,
+            n => (1 << n) - 1,
+        }
+    }
+}
+
+/// Add three one-bit numbers for 64 cells at a time, returning the sum and carry bits.
+#[inline]
+fn full_adder(a: u64, b: u64, c: u64) -> (u64, u64) {
+    let a_xor_b = a ^ b;
+    (a_xor_b ^ c, (a & b) | (c & a_xor_b))
+}
+
+/// Add two one-bit numbers for 64 cells at a time, returning the sum and carry bits.
+#[inline]
+fn half_adder(a: u64, b: u64) -> (u64, u64) {
+    (a ^ b, a & b)
+}
+
+/// The words of a row, shifted so that bit `x` holds the cell to the left and to the right of
+/// cell `x`.
+/// `left` and `right` are the cells just outside of the row, which are shifted in at the edges.
+#[inline]
+pub(crate) fn shifted_words(
+    row: &[u64],
+    i: usize,
+    width: usize,
+    left: u64,
+    right: u64,
+) -> (u64, u64) {
+    let word = row[i];
+
+    let previous = if i == 0 { left } else { row[i - 1] >> 63 };
+    let next = if i + 1 == row.len() {
+        right << ((width - 1) % WORD_BITS)
+    } else {
+        row[i + 1] << 63
+    };
+
+    ((word << 1) | previous, (word >> 1) | next)
+}
+
+/// Count the alive neighbours of 64 cells at a time, using a bit-sliced adder.
+/// Takes the words of the row above, the row itself and the row below, each as a triple of the
+/// word shifted left, the word itself and the word shifted right. Returns the four bits of the
+/// neighbour count, least significant bit first.
+#[inline]
+pub(crate) fn count_neighbours(
+    above: (u64, u64, u64),
+    middle: (u64, u64),
+    below: (u64, u64, u64),
+) -> [u64; 4] {
+    let (above_sum, above_carry) = full_adder(above.0, above.1, above.2);
+    let (below_sum, below_carry) = full_adder(below.0, below.1, below.2);
+    let (middle_sum, middle_carry) = half_adder(middle.0, middle.1);
+
+    let (ones, ones_carry) = full_adder(above_sum, below_sum, middle_sum);
+    let (twos_sum, twos_carry) = full_adder(above_carry, below_carry, middle_carry);
+    let (twos, twos_sum_carry) = half_adder(twos_sum, ones_carry);
+    let (fours, eights) = half_adder(twos_carry, twos_sum_carry);
+
+    [ones, twos, fours, eights]
+}
+
+/// Return the mask of the cells whose neighbour count equals `n`.
+#[inline]
+pub(crate) fn count_equals(count: &[u64; 4], n: usize) -> u64 {
+    count.iter().enumerate().fold(!0, |mask, (bit, &word)| {
+        if n >> bit & 1 == 1 {
+            mask & word
+        } else {
+            mask & !word
+        }
+    })
+}
+
+/// Return the cells that are alive in the next generation, for 64 cells at a time.
+/// Takes the neighbour counts, as returned by `count_neighbours`, and the cells that are alive.
+#[inline]
+pub(crate) fn next_generation(rule: &Rule, count: &[u64; 4], alive: u64) -> u64 {
+    (0..9).fold(0, |next, n| {
+        let survives = if rule.survival[n] { alive } else { 0 };
+        let born = if rule.birth[n] { !alive } else { 0 };
+        if survives | born == 0 {
+            next
+        } else {
+            next | (count_equals(count, n) & (survives | born))
+        }
+    })
+}
+
+/// Return the cells that are alive in the next generation of an isotropic non-totalistic rule,
+/// for 64 cells at a time.
+/// Takes the words of the row above, the row itself and the row below, each as a triple of the
+/// word shifted left, the word itself and the word shifted right.
+#[inline]
+pub(crate) fn next_generation_isotropic(
+    isotropic: &Isotropic,
+    above: (u64, u64, u64),
+    middle: (u64, u64, u64),
+    below: (u64, u64, u64),
+) -> u64 {
+    // The bits of the neighbours, in the order of `isotropic::NEIGHBOURS`.
+    let neighbours = [
+        above.0, above.1, above.2, middle.0, middle.2, below.0, below.1, below.2,
+    ];
+
+    // Without B0, only cells with alive neighbours can be alive in the next generation.
+    let mut cells = if isotropic.births(0) {
+        !0
+    } else {
+        neighbours.iter().fold(0, |cells, word| cells | word)
+    };
+
+    let mut next = 0;
+    while cells != 0 {
+        let x = cells.trailing_zeros();
+        cells &= cells - 1;
+
+        let arrangement = neighbours
+            .iter()
+            .enumerate()
+            .fold(0, |arrangement, (i, word)| {
+                arrangement | ((word >> x & 1) as u8) << i
+            });
+        let alive = if middle.1 >> x & 1 == 1 {
+            isotropic.survives(arrangement)
+        } else {
+            isotropic.births(arrangement)
+        };
+        next |= (alive as u64) << x;
+    }
+
+    next
+}
+
+/// Return the cells that are alive in the next generation of any rule with a neighbourhood of
+/// range 1, for 64 cells at a time.
+/// Takes the words of the row above, the row itself and the row below, each as a triple of the
+/// word shifted left, the word itself and the word shifted right.
+#[inline]
+pub(crate) fn next_words(
+    rule: &Rule,
+    above: (u64, u64, u64),
+    middle: (u64, u64, u64),
+    below: (u64, u64, u64),
+) -> u64 {
+    // Only the corners can lie outside of a neighbourhood of range 1.
+    let corner = |dx: isize, dy: isize, word: u64| {
+        if rule.neighbourhood.contains(dx, dy, 1) {
+            word
+        } else {
+            0
+        }
+    };
+    let above = (corner(-1, -1, above.0), above.1, corner(1, -1, above.2));
+    let below = (corner(-1, 1, below.0), below.1, corner(1, 1, below.2));
+
+    match rule.isotropic {
+        Some(ref isotropic) => next_generation_isotropic(isotropic, above, middle, below),
+        None => {
+            let count = count_neighbours(above, (middle.0, middle.2), below);
+            next_generation(rule, &count, middle.1)
+        }
+    }
+}
+
+/// Return the cells that are alive in the next generation of a Generations rule, for up to 64
+/// cells at a time, and write the next states of those cells to `next_states`.
+/// Takes the cells that would be alive if dying cells were dead, as returned by `next_words`, the
+/// cells that are alive, and the states of the cells.
+#[inline]
+pub(crate) fn next_generation_states(
+    rule: &Rule,
+    next: u64,
+    alive: u64,
+    states: &[u8],
+    next_states: &mut [u8],
+) -> u64 {
+    // Dying cells can not be born, only dead ones.
+    let dead = states
+        .iter()
+        .enumerate()
+        .filter(|&(_, &state)| state == 0)
+        .fold(0, |dead, (x, _)| dead | 1 << x);
+    let next = next & (alive | dead);
+
+    for (x, (&state, next_state)) in states.iter().zip(next_states).enumerate() {
+        *next_state = if next >> x & 1 == 1 {
+            1
+        } else if state == 0 || u16::from(state) + 1 >= u16::from(rule.states) {
+            0
+        } else {
+            state + 1
+        };
+    }
+
+    next
+}
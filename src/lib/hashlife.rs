@@ -0,0 +1,609 @@
+use super::automaton::{self, Automaton};
+use super::isotropic;
+use super::parsers::Pattern;
+use super::rule::Rule;
+use super::{Bounds, CellState};
+use std::collections::HashMap;
+
+/// The highest level of the root node, so that the positions in the universe fit in `isize`.
+const MAX_LEVEL: u32 = 61;
+/// The largest `k` for which `HashLife::step_pow2` advances by `2^k` generations, because the
+/// universe is expanded to at least level `k + 3` first.
+pub const MAX_STEP_POW2: u32 = MAX_LEVEL - 3;
+
+/// The id of the dead leaf node.
+const DEAD: usize = 0;
+/// The id of the alive leaf node.
+const ALIVE: usize = 1;
+
+/// A node of the quadtree: either a single cell, or a square of four nodes of the level below.
+/// A node of level `n` is a square of `2^n` by `2^n` cells.
+#[derive(Debug, Clone, Copy)]
+enum Node {
+    Leaf,
+    Branch {
+        level: u32,
+        /// The north-west, north-east, south-west and south-east quadrants.
+        children: [usize; 4],
+        population: u64,
+    },
+}
+
+/// A HashLife engine, which stores the universe as a quadtree of shared nodes and memoizes how
+/// they evolve, so a pattern can be advanced by `2^k` generations at once.
+/// The universe is unbounded, apart from the limits of `isize` coordinates.
+///
+/// Nodes are never freed, so the memory usage grows with the number of distinct nodes that have
+/// been seen.
+pub struct HashLife {
+    rule: Rule,
+    nodes: Vec<Node>,
+    /// Finds the id of the branch node with the given children.
+    branches: HashMap<[usize; 4], usize>,
+    /// The results of advancing a node by `2^k` generations, keyed by the node and `k`.
+    results: HashMap<(usize, u32), usize>,
+    /// The empty node of every level.
+    empty: Vec<usize>,
+    root: usize,
+    /// The position of the top-left corner of the root node.
+    origin: (isize, isize),
+    generation: u64,
+}
+
+impl HashLife {
+    /// Return a new, empty universe that evolves using the given rule.
+    /// Rules in which dead cells with no alive neighbours are born (`B0`) are not supported,
+    /// because they would fill the unbounded universe. Generations rules are not supported either,
+    /// because only alive and dead cells are stored, and neither are Larger than Life rules and
+    /// rule tables.
+    pub fn new(rule: Rule) -> Result<HashLife, String> {
+        if rule.birth[0] {
+            return Err(format!(
+                "The rule `{}` can not be used with HashLife, because it contains B0.",
+                rule
+            ));
+        }
+        if rule.states > 2 {
+            return Err(format!(
+                "The rule `{}` can not be used with HashLife, because it has dying states.",
+                rule
+            ));
+        }
+        if rule.table.is_some() {
+            return Err(format!(
+                "The rule `{}` can not be used with HashLife, because it is a rule table.",
+                rule
+            ));
+        }
+        if rule.larger_than_life.is_some() {
+            return Err(format!(
+                "The rule `{}` can not be used with HashLife, because it is a Larger than Life rule.",
+                rule
+            ));
+        }
+
+        let mut hashlife = HashLife {
+            rule,
+            nodes: vec![Node::Leaf, Node::Leaf],
+            branches: HashMap::new(),
+            results: HashMap::new(),
+            empty: vec![DEAD],
+            root: DEAD,
+            origin: (0, 0),
+            generation: 0,
+        };
+
+        hashlife.root = hashlife.empty_node(3);
+        hashlife.origin = (-4, -4);
+
+        Ok(hashlife)
+    }
+
+    /// Return a new universe with the cells of the pattern.
+    /// If the pattern declares a rule, that rule is used, otherwise Conway's rule is used.
+    /// Returns an error if the pattern has cells in states other than alive, because only alive
+    /// and dead cells are stored.
+    pub fn from_pattern(pattern: &Pattern) -> Result<HashLife, String> {
+        if pattern.states.iter().any(|&state| state != 1) {
+            return Err(String::from(
+                "The pattern can not be used with HashLife, because it has cells in other states than alive.",
+            ));
+        }
+        let rule = match pattern.rule {
+            Some(ref rule) => rule.parse()?,
+            None => Rule::default(),
+        };
+
+        let mut hashlife = HashLife::new(rule)?;
+        for &(x, y) in &pattern.cells {
+            hashlife.set(x, y, CellState::Alive);
+        }
+
+        Ok(hashlife)
+    }
+
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    /// Advance the universe by `2^k` generations at once, where `k` is at most `MAX_STEP_POW2`.
+    /// Returns an error if `k` is larger, or if the alive cells have spread so far that the
+    /// universe would not fit in `isize` coordinates. The universe is not advanced then.
+    pub fn step_pow2(&mut self, k: u32) -> Result<(), String> {
+        if k > MAX_STEP_POW2 {
+            return Err(format!(
+                "Can not advance by 2^{} generations at once, at most by 2^{}.",
+                k, MAX_STEP_POW2
+            ));
+        }
+
+        // Grow the universe until the alive cells can not reach the edge of the result in `2^k`
+        // generations.
+        while self.level(self.root) < k + 2 || !self.is_padded(self.root) {
+            self.grow()?;
+        }
+        self.grow()?;
+
+        let level = self.level(self.root);
+        self.root = self.advance(self.root, k);
+
+        let quarter = 1 << (level - 2);
+        self.origin = (self.origin.0 + quarter, self.origin.1 + quarter);
+        self.generation += 1 << k;
+
+        Ok(())
+    }
+
+    fn level(&self, node: usize) -> u32 {
+        match self.nodes[node] {
+            Node::Leaf => 0,
+            Node::Branch { level, .. } => level,
+        }
+    }
+
+    fn population_of(&self, node: usize) -> u64 {
+        match self.nodes[node] {
+            Node::Leaf => (node == ALIVE) as u64,
+            Node::Branch { population, .. } => population,
+        }
+    }
+
+    fn children(&self, node: usize) -> [usize; 4] {
+        match self.nodes[node] {
+            Node::Leaf => panic!("A leaf node has no children."),
+            Node::Branch { children, .. } => children,
+        }
+    }
+
+    /// Return the id of the branch node with the given children, creating it if it does not exist.
+    fn branch(&mut self, children: [usize; 4]) -> usize {
+        if let Some(&node) = self.branches.get(&children) {
+            return node;
+        }
+
+        let level = self.level(children[0]) + 1;
+        let population = children.iter().map(|&c| self.population_of(c)).sum();
+
+        let node = self.nodes.len();
+        self.nodes.push(Node::Branch {
+            level,
+            children,
+            population,
+        });
+        self.branches.insert(children, node);
+
+        node
+    }
+
+    fn empty_node(&mut self, level: u32) -> usize {
+        while self.empty.len() <= level as usize {
+            let empty = *self.empty.last().unwrap();
+            let node = self.branch([empty; 4]);
+            self.empty.push(node);
+        }
+
+        self.empty[level as usize]
+    }
+
+    /// Expand the universe, unless it would grow too large for `isize` coordinates.
+    fn grow(&mut self) -> Result<(), String> {
+        if self.level(self.root) >= MAX_LEVEL {
+            return Err(String::from(
+                "The universe has grown too large for isize coordinates.",
+            ));
+        }
+        self.expand();
+
+        Ok(())
+    }
+
+    /// Double the size of the universe, keeping the current root in the center.
+    fn expand(&mut self) {
+        let level = self.level(self.root);
+        let empty = self.empty_node(level - 1);
+        let [nw, ne, sw, se] = self.children(self.root);
+
+        let nw = self.branch([empty, empty, empty, nw]);
+        let ne = self.branch([empty, empty, ne, empty]);
+        let sw = self.branch([empty, sw, empty, empty]);
+        let se = self.branch([se, empty, empty, empty]);
+        self.root = self.branch([nw, ne, sw, se]);
+
+        let quarter = 1 << (level - 1);
+        self.origin = (self.origin.0 - quarter, self.origin.1 - quarter);
+    }
+
+    /// The position of a cell relative to the top-left corner of the universe, if the cell lies in
+    /// the universe.
+    fn position(&self, x: isize, y: isize) -> Option<(isize, isize)> {
+        let size = 1 << self.level(self.root);
+        match (x.checked_sub(self.origin.0), y.checked_sub(self.origin.1)) {
+            (Some(x), Some(y)) if x >= 0 && y >= 0 && x < size && y < size => Some((x, y)),
+            _ => None,
+        }
+    }
+
+    /// Whether all alive cells of the node lie in its center, which is half as wide.
+    fn is_padded(&self, node: usize) -> bool {
+        let [nw, ne, sw, se] = self.children(node);
+        let inner = self.population_of(self.children(nw)[3])
+            + self.population_of(self.children(ne)[2])
+            + self.population_of(self.children(sw)[1])
+            + self.population_of(self.children(se)[0]);
+
+        inner == self.population_of(node)
+    }
+
+    fn set_in(&mut self, node: usize, x: isize, y: isize, leaf: usize) -> usize {
+        let level = self.level(node);
+        if level == 0 {
+            return leaf;
+        }
+
+        let half = 1 << (level - 1);
+        let quadrant = (x >= half) as usize + 2 * (y >= half) as usize;
+        let mut children = self.children(node);
+        children[quadrant] = self.set_in(children[quadrant], x % half, y % half, leaf);
+
+        self.branch(children)
+    }
+
+    fn bounds_of(&self, node: usize, memo: &mut HashMap<usize, Option<Bounds>>) -> Option<Bounds> {
+        if self.population_of(node) == 0 {
+            return None;
+        }
+        if let Some(&bounds) = memo.get(&node) {
+            return bounds;
+        }
+
+        let bounds = match self.nodes[node] {
+            Node::Leaf => Some(((0, 0), (0, 0))),
+            Node::Branch {
+                level, children, ..
+            } => {
+                let half = 1 << (level - 1);
+                let mut bounds: Option<Bounds> = None;
+                for (quadrant, &child) in children.iter().enumerate() {
+                    let offset = (
+                        half * (quadrant % 2) as isize,
+                        half * (quadrant / 2) as isize,
+                    );
+                    if let Some(((min_x, min_y), (max_x, max_y))) = self.bounds_of(child, memo) {
+                        let (min_x, min_y) = (min_x + offset.0, min_y + offset.1);
+                        let (max_x, max_y) = (max_x + offset.0, max_y + offset.1);
+                        bounds = Some(match bounds {
+                            None => ((min_x, min_y), (max_x, max_y)),
+                            Some(((a, b), (c, d))) => {
+                                ((a.min(min_x), b.min(min_y)), (c.max(max_x), d.max(max_y)))
+                            }
+                        });
+                    }
+                }
+                bounds
+            }
+        };
+
+        memo.insert(node, bounds);
+        bounds
+    }
+
+    /// Call `f` with the position of every alive cell of the node in the region, skipping the
+    /// nodes that are empty or lie outside of the region.
+    fn for_each_alive_cell_in(
+        &self,
+        node: usize,
+        origin: (isize, isize),
+        region: Bounds,
+        f: &mut dyn FnMut(isize, isize),
+    ) {
+        let ((min_x, min_y), (max_x, max_y)) = region;
+        let size = 1 << self.level(node);
+        let outside = origin.0 > max_x
+            || origin.1 > max_y
+            || origin.0 + size <= min_x
+            || origin.1 + size <= min_y;
+        if outside || self.population_of(node) == 0 {
+            return;
+        }
+
+        match self.nodes[node] {
+            Node::Leaf => f(origin.0, origin.1),
+            Node::Branch { children, .. } => {
+                let half = size / 2;
+                for (quadrant, &child) in children.iter().enumerate() {
+                    let origin = (
+                        origin.0 + half * (quadrant % 2) as isize,
+                        origin.1 + half * (quadrant / 2) as isize,
+                    );
+                    self.for_each_alive_cell_in(child, origin, region, f);
+                }
+            }
+        }
+    }
+
+    /// Add the population of the node to the blocks of `block_populations`, without visiting the
+    /// cells of nodes that lie inside of one block.
+    fn add_block_populations(
+        &self,
+        node: usize,
+        origin: (isize, isize),
+        region: Bounds,
+        block: usize,
+        populations: &mut [u32],
+    ) {
+        let ((min_x, min_y), (max_x, max_y)) = region;
+        let size = 1 << self.level(node);
+        let (end_x, end_y) = (origin.0 + size - 1, origin.1 + size - 1);
+        if origin.0 > max_x || origin.1 > max_y || end_x < min_x || end_y < min_y {
+            return;
+        }
+        if self.population_of(node) == 0 {
+            return;
+        }
+
+        let block = block as isize;
+        let inside = origin.0 >= min_x && origin.1 >= min_y && end_x <= max_x && end_y <= max_y;
+        let column = (origin.0 - min_x).div_euclid(block);
+        let row = (origin.1 - min_y).div_euclid(block);
+        if inside && column == (end_x - min_x) / block && row == (end_y - min_y) / block {
+            let (columns, _) = automaton::blocks(region, block as usize);
+            populations[row as usize * columns + column as usize] +=
+                self.population_of(node) as u32;
+            return;
+        }
+
+        if let Node::Branch { children, .. } = self.nodes[node] {
+            let half = size / 2;
+            for (quadrant, &child) in children.iter().enumerate() {
+                let origin = (
+                    origin.0 + half * (quadrant % 2) as isize,
+                    origin.1 + half * (quadrant / 2) as isize,
+                );
+                self.add_block_populations(child, origin, region, block as usize, populations);
+            }
+        }
+    }
+
+    /// The node of one level lower in the center of the given node.
+    fn center(&mut self, node: usize) -> usize {
+        let [nw, ne, sw, se] = self.children(node);
+        let children = [
+            self.children(nw)[3],
+            self.children(ne)[2],
+            self.children(sw)[1],
+            self.children(se)[0],
+        ];
+
+        self.branch(children)
+    }
+
+    /// The node centered between two horizontally adjacent nodes.
+    fn horizontal(&mut self, west: usize, east: usize) -> usize {
+        let (west, east) = (self.children(west), self.children(east));
+
+        self.branch([west[1], east[0], west[3], east[2]])
+    }
+
+    /// The node centered between two vertically adjacent nodes.
+    fn vertical(&mut self, north: usize, south: usize) -> usize {
+        let (north, south) = (self.children(north), self.children(south));
+
+        self.branch([north[2], north[3], south[0], south[1]])
+    }
+
+    /// Return the center of the node, advanced by `2^k` generations.
+    /// The node must be at least of level `k + 2`.
+    fn advance(&mut self, node: usize, k: u32) -> usize {
+        let level = self.level(node);
+        debug_assert!(level >= 2 && k + 2 <= level);
+
+        if self.population_of(node) == 0 {
+            return self.empty_node(level - 1);
+        }
+        if let Some(&result) = self.results.get(&(node, k)) {
+            return result;
+        }
+
+        let result = if level == 2 {
+            self.advance_base(node)
+        } else {
+            let [nw, ne, sw, se] = self.children(node);
+
+            // The nine overlapping nodes of one level lower that cover the node.
+            let n = self.horizontal(nw, ne);
+            let w = self.vertical(nw, sw);
+            let c = self.center(node);
+            let e = self.vertical(ne, se);
+            let s = self.horizontal(sw, se);
+            let nine = [nw, n, ne, w, c, e, sw, s, se];
+
+            // When advancing at full speed, the nine nodes are advanced by half of the
+            // generations, otherwise only their centers are taken.
+            let mut centers = [0; 9];
+            for (center, &node) in centers.iter_mut().zip(nine.iter()) {
+                *center = if k + 2 == level {
+                    self.advance(node, k - 1)
+                } else {
+                    self.center(node)
+                };
+            }
+
+            let remaining = if k + 2 == level { k - 1 } else { k };
+            let mut quadrants = [0; 4];
+            for (quadrant, result) in quadrants.iter_mut().enumerate() {
+                let (x, y) = (quadrant % 2, quadrant / 2);
+                let children = [
+                    centers[y * 3 + x],
+                    centers[y * 3 + x + 1],
+                    centers[(y + 1) * 3 + x],
+                    centers[(y + 1) * 3 + x + 1],
+                ];
+                let node = self.branch(children);
+                *result = self.advance(node, remaining);
+            }
+
+            self.branch(quadrants)
+        };
+
+        self.results.insert((node, k), result);
+        result
+    }
+
+    /// Return the center of a node of level 2, advanced by one generation.
+    fn advance_base(&mut self, node: usize) -> usize {
+        // Read the 4 by 4 cells of the node.
+        let mut cells = [[false; 4]; 4];
+        for (quadrant, &child) in self.children(node).iter().enumerate() {
+            for (i, &leaf) in self.children(child).iter().enumerate() {
+                let x = 2 * (quadrant % 2) + i % 2;
+                let y = 2 * (quadrant / 2) + i / 2;
+                cells[y][x] = leaf == ALIVE;
+            }
+        }
+
+        let mut children = [DEAD; 4];
+        for (i, child) in children.iter_mut().enumerate() {
+            let (x, y) = (1 + i % 2, 1 + i / 2);
+
+            let neighbours = isotropic::NEIGHBOURS
+                .iter()
+                .enumerate()
+                .filter(|&(_, &(dx, dy))| {
+                    cells[(y as isize + dy) as usize][(x as isize + dx) as usize]
+                })
+                .fold(0, |neighbours, (i, _)| neighbours | 1 << i);
+
+            if self.rule.next_cell(cells[y][x], neighbours) {
+                *child = ALIVE;
+            }
+        }
+
+        self.branch(children)
+    }
+}
+
+impl Automaton for HashLife {
+    /// Advance the universe by one generation.
+    /// Panics if the alive cells have spread too far for `isize` coordinates.
+    fn step(&mut self) {
+        if let Err(e) = self.step_pow2(0) {
+            panic!("{}", e);
+        }
+    }
+
+    /// Advance the universe by the given number of generations, using the binary representation of
+    /// the number to advance by powers of two. Powers larger than `2^MAX_STEP_POW2` are split in
+    /// steps of `2^MAX_STEP_POW2` generations.
+    /// Panics if the alive cells spread too far for `isize` coordinates.
+    fn step_n(&mut self, generations: u64) {
+        for k in 0..64 {
+            if generations >> k & 1 == 0 {
+                continue;
+            }
+
+            let (k, times) = if k > MAX_STEP_POW2 {
+                (MAX_STEP_POW2, 1 << (k - MAX_STEP_POW2))
+            } else {
+                (k, 1)
+            };
+            for _ in 0..times {
+                if let Err(e) = self.step_pow2(k) {
+                    panic!("{}", e);
+                }
+            }
+        }
+    }
+
+    fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    fn population(&self) -> u64 {
+        self.population_of(self.root)
+    }
+
+    fn get(&self, x: isize, y: isize) -> CellState {
+        let (x, y) = match self.position(x, y) {
+            Some(position) => position,
+            None => return CellState::Dead,
+        };
+
+        let mut node = self.root;
+        let (mut x, mut y) = (x, y);
+        while let Node::Branch {
+            level, children, ..
+        } = self.nodes[node]
+        {
+            let half = 1 << (level - 1);
+            let quadrant = (x >= half) as usize + 2 * (y >= half) as usize;
+            node = children[quadrant];
+            x %= half;
+            y %= half;
+        }
+
+        (node == ALIVE).into()
+    }
+
+    /// Cells that lie so far out that the universe would not fit in `isize` coordinates are left
+    /// dead.
+    fn set(&mut self, x: isize, y: isize, state: CellState) {
+        let (x, y) = loop {
+            if let Some(position) = self.position(x, y) {
+                break position;
+            }
+            if self.grow().is_err() {
+                return;
+            }
+        };
+
+        let leaf = if state == CellState::Alive {
+            ALIVE
+        } else {
+            DEAD
+        };
+        self.root = self.set_in(self.root, x, y, leaf);
+    }
+
+    fn bounds(&self) -> Option<Bounds> {
+        let mut memo = HashMap::new();
+        self.bounds_of(self.root, &mut memo)
+            .map(|((min_x, min_y), (max_x, max_y))| {
+                (
+                    (self.origin.0 + min_x, self.origin.1 + min_y),
+                    (self.origin.0 + max_x, self.origin.1 + max_y),
+                )
+            })
+    }
+
+    fn for_each_alive_cell(&self, region: Bounds, f: &mut dyn FnMut(isize, isize)) {
+        self.for_each_alive_cell_in(self.root, self.origin, region, f);
+    }
+
+    fn block_populations(&self, region: Bounds, block: usize) -> Vec<u32> {
+        let (columns, rows) = automaton::blocks(region, block);
+        let mut populations = vec![0; columns * rows];
+        self.add_block_populations(self.root, self.origin, region, block, &mut populations);
+
+        populations
+    }
+}
@@ -0,0 +1,217 @@
+use super::board::Board;
+use std::collections::VecDeque;
+use std::ops::BitXor;
+
+/// The largest number of generations between two snapshots that store the whole board.
+const KEYFRAME_INTERVAL: usize = 32;
+
+/// A stored generation of a board.
+#[derive(Debug, Clone)]
+enum Snapshot {
+    /// The whole board.
+    Keyframe(Board),
+    /// The words and states that differ from the generation before, as their index and the
+    /// exclusive or of the old and new value.
+    Delta {
+        words: Vec<(usize, u64)>,
+        states: Vec<(usize, u8)>,
+    },
+}
+
+/// A bounded history of the previous generations of a board, so that a game of life can step
+/// back.
+/// Every few generations the whole board is stored, and the generations in between only store
+/// what changed. Once the history is full, the oldest generations are dropped.
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    capacity: usize,
+    /// The generation of the oldest snapshot.
+    first_generation: u64,
+    snapshots: VecDeque<Snapshot>,
+    /// The board of the newest snapshot, which the next snapshot is compared to.
+    last: Option<Board>,
+    /// The number of snapshots since the newest keyframe.
+    since_keyframe: usize,
+}
+
+impl History {
+    /// Return an empty history that keeps at most `capacity` generations.
+    /// A history with a capacity of 0 keeps nothing.
+    pub fn new(capacity: usize) -> History {
+        History {
+            capacity,
+            ..History::default()
+        }
+    }
+
+    /// The largest number of generations that are kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of generations that are kept.
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// The oldest generation that can be restored.
+    pub fn oldest(&self) -> Option<u64> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.first_generation)
+        }
+    }
+
+    /// The newest generation that can be restored.
+    pub fn newest(&self) -> Option<u64> {
+        self.oldest()
+            .map(|first| first + self.snapshots.len() as u64 - 1)
+    }
+
+    /// Forget all generations.
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+        self.last = None;
+        self.since_keyframe = 0;
+    }
+
+    /// Store the board of a generation.
+    /// If the generation does not directly follow the newest generation, the history is cleared
+    /// first.
+    pub fn push(&mut self, generation: u64, board: &Board) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.newest().map(|newest| newest + 1) != Some(generation) {
+            self.clear();
+            self.first_generation = generation;
+        }
+
+        let snapshot = match self.last {
+            Some(ref last)
+                if self.since_keyframe + 1 < KEYFRAME_INTERVAL && same_shape(last, board) =>
+            {
+                self.since_keyframe += 1;
+                delta(last, board)
+            }
+            _ => {
+                self.since_keyframe = 0;
+                Snapshot::Keyframe(board.clone())
+            }
+        };
+        self.snapshots.push_back(snapshot);
+        match self.last {
+            Some(ref mut last) if same_shape(last, board) => last.clone_from(board),
+            _ => self.last = Some(board.clone()),
+        }
+
+        while self.snapshots.len() > self.capacity {
+            self.pop_oldest();
+        }
+    }
+
+    /// Return the board of a generation, or `None` if it is not kept.
+    pub fn get(&self, generation: u64) -> Option<Board> {
+        let index = self.index(generation)?;
+        let keyframe = (0..index + 1)
+            .rev()
+            .find(|&i| match self.snapshots[i] {
+                Snapshot::Keyframe(_) => true,
+                Snapshot::Delta { .. } => false,
+            })
+            .expect("The oldest snapshot is always a keyframe.");
+
+        let mut board = match self.snapshots[keyframe] {
+            Snapshot::Keyframe(ref board) => board.clone(),
+            Snapshot::Delta { .. } => unreachable!(),
+        };
+        for snapshot in self.snapshots.iter().take(index + 1).skip(keyframe + 1) {
+            apply(&mut board, snapshot);
+        }
+
+        Some(board)
+    }
+
+    /// Forget the given generation and all generations after it.
+    pub fn truncate(&mut self, generation: u64) {
+        if generation <= self.first_generation {
+            self.clear();
+        } else if let Some(index) = self.index(generation) {
+            self.snapshots.truncate(index);
+            self.last = None;
+            self.since_keyframe = 0;
+        }
+    }
+
+    fn index(&self, generation: u64) -> Option<usize> {
+        match (self.oldest(), self.newest()) {
+            (Some(oldest), Some(newest)) if (oldest..=newest).contains(&generation) => {
+                Some((generation - oldest) as usize)
+            }
+            _ => None,
+        }
+    }
+
+    /// Drop the oldest snapshot, turning the snapshot after it into a keyframe.
+    fn pop_oldest(&mut self) {
+        let oldest = self.snapshots.pop_front();
+        self.first_generation += 1;
+
+        if let (Some(Snapshot::Keyframe(mut board)), Some(next)) =
+            (oldest, self.snapshots.front_mut())
+        {
+            if let Snapshot::Delta { .. } = *next {
+                apply(&mut board, next);
+                *next = Snapshot::Keyframe(board);
+            }
+        }
+    }
+}
+
+/// Whether the words and states of two boards can be compared one by one.
+fn same_shape(a: &Board, b: &Board) -> bool {
+    a.width() == b.width() && a.height() == b.height() && a.is_multi_state() == b.is_multi_state()
+}
+
+/// The differences between two boards of the same shape.
+fn delta(old: &Board, new: &Board) -> Snapshot {
+    let (old_words, old_states) = old.words_and_states();
+    let (new_words, new_states) = new.words_and_states();
+
+    Snapshot::Delta {
+        words: changes(old_words, new_words),
+        states: changes(old_states, new_states),
+    }
+}
+
+/// The indices of the values that differ, with the exclusive or of the old and new value.
+fn changes<T: Copy + PartialEq + BitXor<Output = T>>(old: &[T], new: &[T]) -> Vec<(usize, T)> {
+    old.iter()
+        .zip(new)
+        .enumerate()
+        .filter(|&(_, (a, b))| a != b)
+        .map(|(i, (&a, &b))| (i, a ^ b))
+        .collect()
+}
+
+/// Apply the differences of a delta to the board of the generation before it.
+fn apply(board: &mut Board, snapshot: &Snapshot) {
+    if let Snapshot::Delta {
+        words: ref word_changes,
+        states: ref state_changes,
+    } = *snapshot
+    {
+        let (words, states) = board.words_and_states_mut();
+        for &(i, change) in word_changes {
+            words[i] ^= change;
+        }
+        for &(i, change) in state_changes {
+            states[i] ^= change;
+        }
+    }
+}
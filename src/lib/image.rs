@@ -0,0 +1,351 @@
+use super::automaton::{self, Automaton};
+use super::png;
+use super::{Bounds, CellState};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+/// How a pixel is colored when the cells are so small that several cells share it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Shading {
+    /// Blend the foreground and background colors by the part of the cells that are alive.
+    #[default]
+    Density,
+    /// Use the foreground color if any of the cells is alive.
+    AnyAlive,
+}
+
+impl FromStr for Shading {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "density" => Ok(Shading::Density),
+            "any" => Ok(Shading::AnyAlive),
+            _ => Err(format!(
+                "Unknown shading `{}`, expected `density` or `any`.",
+                s
+            )),
+        }
+    }
+}
+
+/// How the cells are drawn: the colors, as red, green, blue and alpha components from 0 to 1,
+/// the shading and the border.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Style {
+    pub foreground: [f32; 4],
+    pub background: [f32; 4],
+    pub shading: Shading,
+    /// Whether the cells at the edges of the region are drawn in the foreground color.
+    pub border: bool,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style {
+            foreground: [0.0, 0.0, 0.0, 1.0],
+            background: [1.0, 1.0, 1.0, 1.0],
+            shading: Shading::default(),
+            border: false,
+        }
+    }
+}
+
+/// The cells of a board that are drawn, and the size they are drawn at. The window, the terminal
+/// and the images and GIFs are all drawn from a viewport, so the same viewport frames the same
+/// cells in each of them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    /// The cells that are drawn, which includes both corners.
+    pub region: Bounds,
+    /// The width and height of a cell in pixels, which may be less than one.
+    pub cell_width: f32,
+}
+
+impl Viewport {
+    pub fn new(region: Bounds, cell_width: f32) -> Viewport {
+        Viewport { region, cell_width }
+    }
+
+    /// The number of columns and rows of cells in the region.
+    pub fn cells(&self) -> (usize, usize) {
+        let ((min_x, min_y), (max_x, max_y)) = self.region;
+        (
+            (max_x - min_x + 1).max(0) as usize,
+            (max_y - min_y + 1).max(0) as usize,
+        )
+    }
+
+    /// The number of cells on the width and height of the blocks that share a pixel, when cells
+    /// are smaller than a pixel. Otherwise this is 1.
+    pub fn block(&self) -> usize {
+        if self.cell_width < 1.0 {
+            (1.0 / self.cell_width).ceil() as usize
+        } else {
+            1
+        }
+    }
+}
+
+/// An image of red, green and blue pixels, row after row from the top-left corner.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[u8; 3]>,
+}
+
+impl Image {
+    /// Create an image that is filled with one color.
+    pub fn new(width: usize, height: usize, color: [u8; 3]) -> Self {
+        Image {
+            width,
+            height,
+            pixels: vec![color; width * height],
+        }
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 3] {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Fill a rectangle, given by its top-left corner and size, with a color. The parts that lie
+    /// outside of the image are left out.
+    pub fn fill_rect(&mut self, x: isize, y: isize, width: usize, height: usize, color: [u8; 3]) {
+        let clamp = |v: isize, max: usize| v.max(0).min(max as isize) as usize;
+        let (min_x, max_x) = (clamp(x, self.width), clamp(x + width as isize, self.width));
+        let (min_y, max_y) = (
+            clamp(y, self.height),
+            clamp(y + height as isize, self.height),
+        );
+
+        for row in min_y..max_y {
+            for pixel in &mut self.pixels[row * self.width + min_x..row * self.width + max_x] {
+                *pixel = color;
+            }
+        }
+    }
+
+    /// Write the image as a binary PPM file.
+    pub fn write_ppm<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(writer, "P6\n{} {}\n255\n", self.width, self.height)?;
+        writer.write_all(&self.bytes())
+    }
+
+    /// Write the image as a PNG file.
+    pub fn write_png<W: Write>(&self, writer: W) -> Result<(), String> {
+        use self::png::HasParameters;
+
+        let mut encoder = png::Encoder::new(writer, self.width as u32, self.height as u32);
+        encoder.set(png::ColorType::RGB).set(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&self.bytes()))
+            .map_err(|e| e.to_string())
+    }
+
+    /// Write the image to a file, as PNG or PPM depending on the extension of the path.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase());
+        let png = match extension.as_deref() {
+            Some("png") => true,
+            Some("ppm") => false,
+            _ => {
+                return Err(format!(
+                    "Unknown image format of `{}`, expected a .png or .ppm file.",
+                    path.display()
+                ))
+            }
+        };
+
+        let file = File::create(path).map_err(|e| e.to_string())?;
+        let mut writer = BufWriter::new(file);
+        if png {
+            self.write_png(&mut writer)?;
+        } else {
+            self.write_ppm(&mut writer).map_err(|e| e.to_string())?;
+        }
+        writer.flush().map_err(|e| e.to_string())
+    }
+
+    /// The red, green and blue components of all pixels, one after the other.
+    pub fn bytes(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|pixel| pixel.iter().cloned())
+            .collect()
+    }
+}
+
+/// Draw the cells of the viewport of an automaton in an image.
+/// When cells are smaller than a pixel, every block of cells that shares a pixel is drawn as one
+/// square, colored by the shading of the style.
+pub fn render(automaton: &dyn Automaton, viewport: &Viewport, style: &Style) -> Image {
+    let region = viewport.region;
+    let ((min_x, min_y), (max_x, max_y)) = region;
+    let (columns, rows) = viewport.cells();
+    let cell_width = viewport.cell_width;
+
+    let mut image = Image::new(
+        ((columns as f32 * cell_width) as usize).max(1),
+        ((rows as f32 * cell_width) as usize).max(1),
+        to_rgb(style.background),
+    );
+    if columns == 0 || rows == 0 {
+        return image;
+    }
+
+    // Fill `width` by `height` cells, starting at a position on the board.
+    let fill = |image: &mut Image, x: isize, y: isize, width: usize, height: usize, color| {
+        let (x, y) = ((x - min_x) as f32, (y - min_y) as f32);
+        let (from_x, to_x) = (x * cell_width, (x + width as f32) * cell_width);
+        let (from_y, to_y) = (y * cell_width, (y + height as f32) * cell_width);
+        image.fill_rect(
+            from_x.floor() as isize,
+            from_y.floor() as isize,
+            (to_x.floor() - from_x.floor()) as usize,
+            (to_y.floor() - from_y.floor()) as usize,
+            to_rgb(color),
+        );
+    };
+
+    let block = viewport.block();
+    if block > 1 {
+        for block in alive_blocks(automaton, region, block) {
+            let color = mix(
+                style.background,
+                style.foreground,
+                block.shade(style.shading),
+            );
+            fill(
+                &mut image,
+                block.x,
+                block.y,
+                block.width,
+                block.height,
+                color,
+            );
+        }
+    } else {
+        let states = automaton.states();
+        automaton.for_each_cell(region, &mut |x, y, state| {
+            let color = state_color(style, automaton, states, state);
+            fill(&mut image, x, y, 1, 1, color);
+        });
+    }
+
+    if style.border {
+        fill(&mut image, min_x, min_y, columns, 1, style.foreground);
+        fill(&mut image, min_x, max_y, columns, 1, style.foreground);
+        fill(&mut image, min_x, min_y, 1, rows, style.foreground);
+        fill(&mut image, max_x, min_y, 1, rows, style.foreground);
+    }
+
+    image
+}
+
+/// A block of cells that share a pixel when zoomed out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Block {
+    /// The position of the top-left cell of the block.
+    pub x: isize,
+    pub y: isize,
+    /// The number of columns and rows of cells in the block, which are fewer than the block size
+    /// if the block is cut off by the edge of the region.
+    pub width: usize,
+    pub height: usize,
+    /// The number of alive cells in the block.
+    pub population: u32,
+}
+
+impl Block {
+    /// How far the color of the block lies from the background to the foreground color.
+    pub fn shade(&self, shading: Shading) -> f32 {
+        match shading {
+            Shading::Density => self.population as f32 / (self.width * self.height) as f32,
+            Shading::AnyAlive => 1.0,
+        }
+    }
+}
+
+/// The blocks of `block` by `block` cells with alive cells that cover a region, which includes
+/// both of its corners. The blocks are aligned to multiples of `block` in the coordinates of the
+/// automaton, so they stay the same while the region moves. The blocks at the left and top of
+/// the region may start before it, the blocks at the right and bottom are cut off by it.
+pub fn alive_blocks(automaton: &dyn Automaton, region: Bounds, block: usize) -> Vec<Block> {
+    let ((min_x, min_y), (max_x, max_y)) = region;
+    let size = block as isize;
+    let (left, top) = (min_x.div_euclid(size) * size, min_y.div_euclid(size) * size);
+    let aligned = ((left, top), (max_x, max_y));
+    let (columns, _) = automaton::blocks(aligned, block);
+
+    automaton
+        .block_populations(aligned, block)
+        .into_iter()
+        .enumerate()
+        .filter(|&(_, population)| population > 0)
+        .map(|(i, population)| {
+            let x = left + (i % columns * block) as isize;
+            let y = top + (i / columns * block) as isize;
+            Block {
+                x,
+                y,
+                width: block.min((max_x - x + 1) as usize),
+                height: block.min((max_y - y + 1) as usize),
+                population,
+            }
+        })
+        .collect()
+}
+
+/// The color of a cell in the given state. States with a color declared by the rule have that
+/// color, alive cells have the foreground color, and dying cells fade from the foreground to the
+/// background color as they get older.
+pub fn state_color(
+    style: &Style,
+    automaton: &dyn Automaton,
+    states: u8,
+    state: CellState,
+) -> [f32; 4] {
+    if let Some(color) = automaton.state_color(state.into()) {
+        return [
+            f32::from(color[0]) / 255.0,
+            f32::from(color[1]) / 255.0,
+            f32::from(color[2]) / 255.0,
+            1.0,
+        ];
+    }
+
+    match state {
+        CellState::Dying(age) => {
+            let t = f32::from(age) / f32::from(states.max(2) - 1);
+            mix(style.foreground, style.background, t.min(1.0))
+        }
+        _ => style.foreground,
+    }
+}
+
+/// The color that lies at `t`, from 0 to 1, on the way from one color to another.
+pub fn mix(from: [f32; 4], to: [f32; 4], t: f32) -> [f32; 4] {
+    let mut color = from;
+    for (c, &to) in color.iter_mut().zip(to.iter()) {
+        *c += (to - *c) * t;
+    }
+    color
+}
+
+/// The red, green and blue components of a color, from 0 to 255.
+pub fn to_rgb(color: [f32; 4]) -> [u8; 3] {
+    let component = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    [
+        component(color[0]),
+        component(color[1]),
+        component(color[2]),
+    ]
+}
@@ -0,0 +1,278 @@
+use std::fmt;
+
+/// The positions of the neighbours of a cell, relative to the cell, in the order of the bits of
+/// an arrangement of neighbours: bit `i` of an arrangement is set if neighbour `i` is alive.
+pub const NEIGHBOURS: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// The letters of Hensel notation for the arrangements of 1 to 4 alive neighbours, with an
+/// arrangement that each letter stands for. The letters for 5 to 7 alive neighbours stand for
+/// the arrangements in which the neighbours of the arrangements for 3 to 1 alive neighbours are
+/// dead instead.
+const LETTERS: [&[(char, u8)]; 4] = [
+    &[('c', 1), ('e', 2)],
+    &[
+        ('c', 5),
+        ('e', 10),
+        ('a', 3),
+        ('i', 24),
+        ('k', 17),
+        ('n', 36),
+    ],
+    &[
+        ('c', 37),
+        ('e', 26),
+        ('a', 11),
+        ('i', 7),
+        ('k', 50),
+        ('n', 13),
+        ('j', 14),
+        ('q', 38),
+        ('r', 25),
+        ('y', 49),
+    ],
+    &[
+        ('c', 165),
+        ('e', 90),
+        ('a', 15),
+        ('i', 29),
+        ('k', 51),
+        ('n', 39),
+        ('j', 58),
+        ('q', 54),
+        ('r', 27),
+        ('y', 53),
+        ('t', 57),
+        ('w', 46),
+        ('z', 60),
+    ],
+];
+
+/// A set of arrangements of alive neighbours, with one bit for each of the 256 arrangements.
+type Arrangements = [u64; 4];
+
+/// The arrangements of alive neighbours for which cells are born or survive, under an isotropic
+/// non-totalistic rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Isotropic {
+    birth: Arrangements,
+    survival: Arrangements,
+}
+
+impl Isotropic {
+    /// Parse the birth and survival conditions in Hensel notation, like `3-cnqy` and `23-a`.
+    pub fn parse(birth: &str, survival: &str) -> Result<Isotropic, String> {
+        Ok(Isotropic {
+            birth: parse_conditions(birth)?,
+            survival: parse_conditions(survival)?,
+        })
+    }
+
+    /// Whether a dead cell with the given arrangement of alive neighbours is born.
+    pub fn births(&self, neighbours: u8) -> bool {
+        contains(&self.birth, neighbours)
+    }
+
+    /// Whether an alive cell with the given arrangement of alive neighbours survives.
+    pub fn survives(&self, neighbours: u8) -> bool {
+        contains(&self.survival, neighbours)
+    }
+
+    /// Whether the conditions only depend on the number of alive neighbours, so the rule is an
+    /// outer-totalistic rule.
+    pub fn is_totalistic(&self) -> bool {
+        (0..9).all(|n| letters(&self.birth, n).is_none() && letters(&self.survival, n).is_none())
+    }
+
+    /// For every number of alive neighbours, whether a dead cell is born with some arrangement of
+    /// that many alive neighbours.
+    pub fn birth_counts(&self) -> [bool; 9] {
+        counts(&self.birth)
+    }
+
+    /// For every number of alive neighbours, whether an alive cell survives with some arrangement
+    /// of that many alive neighbours.
+    pub fn survival_counts(&self) -> [bool; 9] {
+        counts(&self.survival)
+    }
+
+    /// Write the birth conditions in Hensel notation.
+    pub fn fmt_birth(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_conditions(&self.birth, f)
+    }
+
+    /// Write the survival conditions in Hensel notation.
+    pub fn fmt_survival(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_conditions(&self.survival, f)
+    }
+}
+
+fn contains(arrangements: &Arrangements, neighbours: u8) -> bool {
+    arrangements[neighbours as usize / 64] >> (neighbours % 64) & 1 == 1
+}
+
+fn insert(arrangements: &mut Arrangements, neighbours: u8) {
+    arrangements[neighbours as usize / 64] |= 1 << (neighbours % 64);
+}
+
+fn counts(arrangements: &Arrangements) -> [bool; 9] {
+    let mut counts = [false; 9];
+    for neighbours in 0..=255u8 {
+        if contains(arrangements, neighbours) {
+            counts[neighbours.count_ones() as usize] = true;
+        }
+    }
+
+    counts
+}
+
+/// The letters that can follow the given number of alive neighbours, with an arrangement that
+/// each letter stands for.
+fn letters_of(n: usize) -> Vec<(char, u8)> {
+    match n {
+        1..=4 => LETTERS[n - 1].to_vec(),
+        5..=7 => LETTERS[7 - n].iter().map(|&(c, a)| (c, !a)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Map an arrangement of alive neighbours to the same arrangement after a transformation of the
+/// positions.
+fn transform(neighbours: u8, f: &dyn Fn((isize, isize)) -> (isize, isize)) -> u8 {
+    (0..8)
+        .filter(|&i| neighbours >> i & 1 == 1)
+        .map(|i| {
+            let position = f(NEIGHBOURS[i]);
+            NEIGHBOURS.iter().position(|&p| p == position).unwrap()
+        })
+        .fold(0, |result, i| result | 1 << i)
+}
+
+/// All arrangements that can be made from an arrangement by rotating and mirroring it.
+fn symmetries(neighbours: u8) -> Vec<u8> {
+    let mut result = Vec::with_capacity(8);
+    let mut current = neighbours;
+    for _ in 0..4 {
+        current = transform(current, &|(x, y)| (-y, x));
+        result.push(current);
+        result.push(transform(current, &|(x, y)| (-x, y)));
+    }
+
+    result
+}
+
+/// The arrangements that a letter stands for.
+fn arrangements_of(n: usize, letter: char) -> Option<Vec<u8>> {
+    letters_of(n)
+        .into_iter()
+        .find(|&(c, _)| c == letter)
+        .map(|(_, neighbours)| symmetries(neighbours))
+}
+
+/// Parse the conditions of a rule in Hensel notation, like `23-a` or `2ce3`.
+fn parse_conditions(s: &str) -> Result<Arrangements, String> {
+    let mut arrangements = [0; 4];
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let n = match c.to_digit(10) {
+            Some(n) if n <= 8 => n as usize,
+            _ => {
+                return Err(format!(
+                    "Unexpected character `{}` in rule, expected a digit from 0 to 8.",
+                    c
+                ))
+            }
+        };
+
+        let negated = chars.peek() == Some(&'-');
+        if negated {
+            chars.next();
+        }
+        let mut letters = Vec::new();
+        while let Some(&letter) = chars.peek().filter(|c| c.is_ascii_lowercase()) {
+            chars.next();
+            letters.push(letter);
+        }
+        if negated && letters.is_empty() {
+            return Err(format!("Expected letters after `{}-` in rule.", n));
+        }
+
+        let mut selected = Vec::new();
+        for &letter in &letters {
+            match arrangements_of(n, letter) {
+                Some(a) => selected.extend(a),
+                None => {
+                    return Err(format!(
+                        "Unexpected letter `{}` after `{}` in rule, expected one of `{}`.",
+                        letter,
+                        n,
+                        letters_of(n).iter().map(|&(c, _)| c).collect::<String>()
+                    ))
+                }
+            }
+        }
+
+        for neighbours in (0..=255u8).filter(|a| a.count_ones() as usize == n) {
+            if letters.is_empty() || selected.contains(&neighbours) != negated {
+                insert(&mut arrangements, neighbours);
+            }
+        }
+    }
+
+    Ok(arrangements)
+}
+
+/// The letters of the arrangements of `n` alive neighbours in the set, or `None` if the set
+/// contains either all or none of them.
+fn letters(arrangements: &Arrangements, n: usize) -> Option<String> {
+    let letters: String = letters_of(n)
+        .into_iter()
+        .filter(|&(_, neighbours)| contains(arrangements, neighbours))
+        .map(|(c, _)| c)
+        .collect();
+
+    if letters.is_empty() || letters.len() == letters_of(n).len() {
+        None
+    } else {
+        Some(letters)
+    }
+}
+
+/// Write the conditions in Hensel notation, using the letters that are excluded instead of the
+/// ones that are included if that is shorter, like `3-cnqy`.
+fn fmt_conditions(arrangements: &Arrangements, f: &mut fmt::Formatter) -> fmt::Result {
+    for n in 0..9 {
+        match letters(arrangements, n) {
+            Some(included) => {
+                let all = letters_of(n);
+                if included.len() * 2 > all.len() {
+                    let excluded: String = all
+                        .iter()
+                        .map(|&(c, _)| c)
+                        .filter(|&c| !included.contains(c))
+                        .collect();
+                    write!(f, "{}-{}", n, excluded)?;
+                } else {
+                    write!(f, "{}{}", n, included)?;
+                }
+            }
+            None if (0..=255u8)
+                .any(|a| a.count_ones() as usize == n && contains(arrangements, a)) =>
+            {
+                write!(f, "{}", n)?
+            }
+            None => {}
+        }
+    }
+
+    Ok(())
+}
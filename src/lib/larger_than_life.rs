@@ -0,0 +1,213 @@
+use super::board::Board;
+use super::neighbourhood::Neighbourhood;
+use super::rayon::prelude::*;
+use super::rule::Rule;
+use super::topology::Topology;
+use super::CellState;
+
+/// The largest range that is supported, like in Golly.
+const MAX_RANGE: usize = 500;
+
+/// The neighbourhood of a Larger than Life rule, and the numbers of alive neighbours for which
+/// cells are born or survive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LargerThanLife {
+    /// How far the neighbourhood extends from its centre, in cells.
+    pub range: usize,
+    /// Whether a cell is counted as its own neighbour.
+    pub middle: bool,
+    /// The smallest and largest number of alive neighbours for which an alive cell survives.
+    pub survival: (usize, usize),
+    /// The smallest and largest number of alive neighbours for which a dead cell is born.
+    pub birth: (usize, usize),
+    pub neighbourhood: Neighbourhood,
+}
+
+impl LargerThanLife {
+    /// Parse a rule in Golly's notation for Larger than Life, like `R5,C0,M1,S34..58,B34..45,NM`.
+    /// Returns the rule and its number of states, which is given by the `C` parameter.
+    pub fn parse(s: &str) -> Result<(LargerThanLife, u8), String> {
+        let mut range = None;
+        let mut states = None;
+        let mut middle = None;
+        let mut survival = None;
+        let mut birth = None;
+        let mut neighbourhood = None;
+
+        for part in s.split(',').map(str::trim) {
+            let mut chars = part.chars();
+            let key = chars.next().map(|c| c.to_ascii_uppercase());
+            let value = chars.as_str();
+            let duplicate = match key {
+                Some('R') => range.replace(parse_number(part, value)?).is_some(),
+                Some('C') => states.replace(parse_number(part, value)?).is_some(),
+                Some('M') => middle.replace(parse_number(part, value)?).is_some(),
+                Some('S') => survival.replace(parse_interval(part, value)?).is_some(),
+                Some('B') => birth.replace(parse_interval(part, value)?).is_some(),
+                Some('N') => neighbourhood.replace(value.parse()?).is_some(),
+                _ => return Err(format!("Unexpected parameter `{}` in rule `{}`.", part, s)),
+            };
+            if duplicate {
+                return Err(format!("Duplicate parameter `{}` in rule `{}`.", part, s));
+            }
+        }
+
+        let missing = |name: &str| format!("Missing parameter `{}` in rule `{}`.", name, s);
+        let range = range.ok_or_else(|| missing("R"))?;
+        if !(1..=MAX_RANGE).contains(&range) {
+            return Err(format!(
+                "The range of rule `{}` should be from 1 to {}.",
+                s, MAX_RANGE
+            ));
+        }
+        let states = match states.ok_or_else(|| missing("C"))? {
+            0..=2 => 2,
+            states if states <= 255 => states as u8,
+            _ => return Err(format!("Rule `{}` has more than 255 states.", s)),
+        };
+        let middle = match middle.ok_or_else(|| missing("M"))? {
+            0 => false,
+            1 => true,
+            _ => return Err(format!("The parameter M of rule `{}` should be 0 or 1.", s)),
+        };
+
+        let larger_than_life = LargerThanLife {
+            range,
+            middle,
+            survival: survival.ok_or_else(|| missing("S"))?,
+            birth: birth.ok_or_else(|| missing("B"))?,
+            neighbourhood: neighbourhood.unwrap_or_default(),
+        };
+
+        Ok((larger_than_life, states))
+    }
+}
+
+fn parse_number(part: &str, value: &str) -> Result<usize, String> {
+    value.parse().map_err(|_| {
+        format!(
+            "Could not parse `{}`, expected a number after the letter.",
+            part
+        )
+    })
+}
+
+/// Parse an interval of neighbour counts, like the `34..58` in `S34..58`.
+fn parse_interval(part: &str, value: &str) -> Result<(usize, usize), String> {
+    let mut bounds = value.split("..");
+    match (bounds.next(), bounds.next(), bounds.next()) {
+        (Some(min), Some(max), None) => {
+            let (min, max) = (parse_number(part, min)?, parse_number(part, max)?);
+            if min > max {
+                return Err(format!("The interval `{}` is empty.", part));
+            }
+            Ok((min, max))
+        }
+        _ => Err(format!(
+            "Could not parse `{}`, expected an interval like `S34..58`.",
+            part
+        )),
+    }
+}
+
+/// Compute the states of all cells of the board in the next generation of a Larger than Life rule,
+/// row after row.
+///
+/// The alive cells in a neighbourhood are counted using prefix sums of the rows of the board,
+/// including the cells within range of its edges. For Moore neighbourhoods, these are summed into
+/// a summed-area table, so every count only takes four lookups, and for other neighbourhoods
+/// every count takes one lookup per row of the neighbourhood.
+pub(crate) fn next_states(
+    rule: &Rule,
+    larger_than_life: &LargerThanLife,
+    topology: Topology,
+    board: &Board,
+) -> Vec<u8> {
+    let (width, height) = (board.width(), board.height());
+    let range = larger_than_life.range;
+    let (padded_width, padded_height) = (width + 2 * range, height + 2 * range);
+    let row_length = padded_width + 1;
+
+    // `sums[y * row_length + x]` is the number of alive cells to the left of `x` in row `y` of the
+    // padded board, of which the first row and column lie `range` cells outside of the board.
+    let mut sums = vec![0u32; row_length * padded_height];
+    sums.par_chunks_mut(row_length)
+        .enumerate()
+        .for_each(|(padded_y, sums)| {
+            let y = padded_y as isize - range as isize;
+            for padded_x in 0..padded_width {
+                let x = padded_x as isize - range as isize;
+                let alive = match topology.map(x, y, width, height) {
+                    Some((x, y)) => board.get(x, y) == CellState::Alive,
+                    None => false,
+                };
+                sums[padded_x + 1] = sums[padded_x] + alive as u32;
+            }
+        });
+
+    // For Moore neighbourhoods, `table[y * row_length + x]` is the number of alive cells above
+    // and to the left of `(x, y)` on the padded board.
+    let table = if larger_than_life.neighbourhood == Neighbourhood::Moore {
+        let mut table = vec![0u32; row_length * (padded_height + 1)];
+        for i in row_length..table.len() {
+            table[i] = table[i - row_length] + sums[i - row_length];
+        }
+        table
+    } else {
+        Vec::new()
+    };
+    // For every row of the neighbourhood, the first and one past the last column of the
+    // neighbourhood, relative to the column `range` cells to the left of its centre.
+    let neighbourhood = larger_than_life.neighbourhood;
+    let r = range as isize;
+    let extents: Vec<(usize, usize)> = (-r..r + 1)
+        .map(|dy| {
+            let mut columns = (-r..r + 1).filter(|&dx| neighbourhood.contains(dx, dy, range));
+            let first = columns.next().unwrap();
+            let last = columns.next_back().unwrap_or(first);
+            ((first + r) as usize, (last + r + 1) as usize)
+        })
+        .collect();
+
+    // The number of alive cells in the neighbourhood of a cell, including the cell itself.
+    let count = |x: usize, y: usize| -> usize {
+        if table.is_empty() {
+            (0..2 * range + 1)
+                .map(|dy| {
+                    let (first, end) = extents[dy];
+                    let row = &sums[(y + dy) * row_length..(y + dy + 1) * row_length];
+                    (row[x + end] - row[x + first]) as usize
+                })
+                .sum()
+        } else {
+            let (top, bottom) = (y * row_length, (y + 2 * range + 1) * row_length);
+            let (left, right) = (x, x + 2 * range + 1);
+            (table[bottom + right] + table[top + left] - table[top + right] - table[bottom + left])
+                as usize
+        }
+    };
+
+    let (birth, survival) = (larger_than_life.birth, larger_than_life.survival);
+    let mut next = vec![0; width * height];
+    next.par_chunks_mut(width)
+        .enumerate()
+        .for_each(|(y, next_row)| {
+            for (x, next_state) in next_row.iter_mut().enumerate() {
+                let state = u8::from(board.get(x, y));
+                let mut neighbours = count(x, y);
+                if state == 1 && !larger_than_life.middle {
+                    neighbours -= 1;
+                }
+
+                *next_state = match state {
+                    0 if (birth.0..=birth.1).contains(&neighbours) => 1,
+                    0 => 0,
+                    1 if (survival.0..=survival.1).contains(&neighbours) => 1,
+                    _ if u16::from(state) + 1 < u16::from(rule.states) => state + 1,
+                    _ => 0,
+                };
+            }
+        });
+
+    next
+}
@@ -1,22 +1,72 @@
+extern crate gif;
+extern crate png;
 extern crate rand;
 extern crate rayon;
 
+pub mod animation;
+pub mod automaton;
+pub mod board;
+pub mod hashlife;
+pub mod history;
+pub mod image;
+pub mod isotropic;
+pub mod larger_than_life;
+pub mod neighbourhood;
 pub mod parsers;
+pub mod period;
+pub mod rule;
+pub mod rule_table;
+pub mod sparse;
+pub mod topology;
 
+use self::automaton::Automaton;
+use self::board::Board;
+use self::history::History;
 use self::rayon::prelude::*;
+use self::rule::Rule;
+use self::topology::Topology;
 use rand::rngs::SmallRng;
 use rand::{FromEntropy, Rng};
-use std::iter;
+use std::borrow::Cow;
+use std::mem;
+
+/// The positions of the top-left and bottom-right cells of a rectangle.
+pub type Bounds = ((isize, isize), (isize, isize));
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CellState {
     Alive,
     Dead,
+    /// A cell of a Generations rule that has stopped being alive, and will be dead once it has
+    /// passed through all dying states. Holds the number of generations since the cell was alive,
+    /// starting at 1.
+    Dying(u8),
+}
+
+impl From<CellState> for u8 {
+    /// The number of the state: 0 for dead, 1 for alive and 2 or more for dying cells.
+    fn from(state: CellState) -> u8 {
+        match state {
+            CellState::Dead => 0,
+            CellState::Alive => 1,
+            CellState::Dying(age) => age.saturating_add(1),
+        }
+    }
+}
+
+impl From<u8> for CellState {
+    fn from(state: u8) -> Self {
+        match state {
+            0 => CellState::Dead,
+            1 => CellState::Alive,
+            _ => CellState::Dying(state - 1),
+        }
+    }
 }
 
-impl Into<bool> for CellState {
-    fn into(self) -> bool {
-        self == CellState::Alive
+impl From<CellState> for bool {
+    fn from(state: CellState) -> bool {
+        state == CellState::Alive
     }
 }
 
@@ -36,40 +86,97 @@ impl std::ops::Not for CellState {
     fn not(self) -> Self::Output {
         match self {
             CellState::Dead => CellState::Alive,
-            CellState::Alive => CellState::Dead,
+            CellState::Alive | CellState::Dying(_) => CellState::Dead,
         }
     }
 }
 
 pub struct GameOfLife {
-    pub board: Vec<Vec<CellState>>,
+    pub board: Board,
+    /// The board that the next generation is written to, so it does not have to be allocated on
+    /// every update.
+    next_board: Board,
     pub width: usize,
     pub height: usize,
     pub name: Option<String>,
+    pub rule: Rule,
+    pub topology: Topology,
+    /// The number of updates since the board was initialised.
+    pub generation: u64,
+    /// The previous generations of the board, to step back to.
+    pub history: History,
 }
 
 impl GameOfLife {
     /// Return a new GameOfLife instance.
     pub fn new(width: usize, height: usize) -> GameOfLife {
-        let board: Vec<Vec<CellState>> = iter::repeat(
-            iter::repeat(CellState::Dead).take(width).collect(),
-        ).take(height)
-            .collect();
-
         GameOfLife {
-            board,
-            width: width as usize,
-            height: height as usize,
+            board: Board::new(width, height),
+            next_board: Board::new(width, height),
+            width,
+            height,
             name: None,
+            rule: Rule::default(),
+            topology: Topology::default(),
+            generation: 0,
+            history: History::default(),
         }
     }
 
+    /// Set the rule that is used to update the board.
+    pub fn with_rule(&mut self, rule: Rule) -> &mut Self {
+        self.rule = rule;
+
+        self
+    }
+
+    /// Keep up to the given number of previous generations, so that the game of life can step
+    /// back. No generations are kept by default.
+    pub fn with_history(&mut self, generations: usize) -> &mut Self {
+        self.history = History::new(generations);
+
+        self
+    }
+
+    /// Copy the board, rule and generation of another game of life. The history is not copied.
+    /// The board may have a different size, so the game of life can be updated afterwards.
+    pub fn copy_from(&mut self, other: &GameOfLife) -> &mut Self {
+        self.board.clone_from(&other.board);
+        if self.next_board.width() != other.width || self.next_board.height() != other.height {
+            self.next_board = Board::new(other.width, other.height);
+        }
+        self.next_board
+            .set_multi_state(other.board.is_multi_state());
+        self.width = other.width;
+        self.height = other.height;
+        self.name.clone_from(&other.name);
+        self.rule.clone_from(&other.rule);
+        self.topology = other.topology;
+        self.generation = other.generation;
+
+        self
+    }
+
+    /// Set the topology that determines how the edges of the board are joined.
+    /// Returns an error if the topology is the sphere and the board is not square.
+    pub fn with_topology(&mut self, topology: Topology) -> Result<&mut Self, String> {
+        if topology == Topology::Sphere && self.width != self.height {
+            return Err(format!(
+                "The sphere topology only works on square boards, but the board is {} by {} cells.",
+                self.width, self.height
+            ));
+        }
+        self.topology = topology;
+
+        Ok(self)
+    }
+
     /// Init board with only dead cells.
     /// All alive cells will be killed.
     pub fn init_empty(&mut self) -> &mut Self {
-        self.board = iter::repeat(iter::repeat(CellState::Dead).take(self.width).collect())
-            .take(self.height)
-            .collect();
+        self.board.clear();
+        self.generation = 0;
+        self.history.clear();
 
         self
     }
@@ -79,10 +186,13 @@ impl GameOfLife {
     /// alive.
     pub fn init_randomly(&mut self, chance: u8) -> &mut Self {
         let mut rng = SmallRng::from_entropy();
+        self.generation = 0;
+        self.history.clear();
 
-        for y in 1..self.height - 1 {
-            for x in 1..self.width - 1 {
-                self.board[y][x] = (rng.gen::<u8>() % 100 <= chance).into();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                self.board
+                    .set(x, y, (rng.gen::<u8>() % 100 <= chance).into());
             }
         }
 
@@ -90,76 +200,335 @@ impl GameOfLife {
     }
 
     /// Init the game of life board from a file.
+    /// If the file declares a rule, the rule of this game of life is set to it.
     pub fn init_with_file<S>(&mut self, filename: S) -> Result<&mut Self, String>
     where
         S: AsRef<str>,
     {
         let pattern = parsers::Pattern::from_file(filename)?;
-        if let Some(name) = pattern.name {
-            self.name = Some(name);
+        self.init_with_pattern(&pattern)
+    }
+
+    /// Init the game of life board with a pattern at its center.
+    /// If the pattern declares a rule, the rule of this game of life is set to it.
+    pub fn init_with_pattern(&mut self, pattern: &parsers::Pattern) -> Result<&mut Self, String> {
+        if let Some(ref name) = pattern.name {
+            self.name = Some(name.clone());
+        }
+        if let Some(ref rule) = pattern.rule {
+            self.rule = rule.parse()?;
         }
 
         self.init_empty();
 
-        let origin = (self.width / 2, self.height / 2);
+        let origin = ((self.width / 2) as isize, (self.height / 2) as isize);
 
-        for (x, y) in pattern.cells {
-            let x = (x + origin.0 as isize) as usize;
-            let y = (y + origin.1 as isize) as usize;
+        pattern.paste(self, origin);
+
+        Ok(self)
+    }
 
-            if x > 0 && x < self.width && y > 0 && y < self.height {
-                self.board[y][x] = CellState::Alive;
+    /// Go back to a previous generation that is kept in the history.
+    /// The generations after it are forgotten, so updating continues from that generation.
+    pub fn rewind(&mut self, generation: u64) -> Result<&mut Self, String> {
+        match self.history.get(generation) {
+            Some(board) => {
+                self.board = board;
+                self.generation = generation;
+                self.history.truncate(generation);
+                Ok(self)
             }
+            None => Err(format!(
+                "Generation {} can not be restored, because it is not kept in the history.",
+                generation
+            )),
         }
+    }
 
-        Ok(self)
+    /// Go back one generation, if it is kept in the history. Returns whether it was.
+    pub fn step_back(&mut self) -> bool {
+        self.generation > 0 && self.rewind(self.generation - 1).is_ok()
     }
 
-    /// Update the board using the game of life rules.
+    /// Update the board using the rule of this game of life.
+    /// The neighbours of 64 cells are counted at a time, using the bits of the board's words.
+    /// Only Generations rules look at the state of every cell, to make dying cells older.
+    /// Larger than Life rules count the neighbours of every cell using prefix sums instead, and
+    /// rule tables look up the transition of every cell.
     pub fn update(&mut self) {
-        // Count neighbours for all cells.
-        let mut neighbours: Vec<Vec<i32>> = iter::repeat(())
-            .take(self.height)
-            .map(|_| iter::repeat(0).take(self.width).collect())
-            .collect();
-
-        neighbours.par_iter_mut().enumerate().for_each(|(y, row)| {
-            row.iter_mut().enumerate().for_each(|(x, cell)| {
-                if x == 0 || y == 0 || x == self.width - 1 || y == self.height - 1 {
-                    return;
-                }
+        let (width, height) = (self.width, self.height);
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        // Generations rules need the state of every cell, other rules only need the alive bits.
+        let rule = self.rule.clone();
+        self.board.set_multi_state(rule.states > 2);
+        self.next_board.set_multi_state(rule.states > 2);
+        self.history.push(self.generation, &self.board);
+
+        let states = if let Some(ref table) = rule.table {
+            Some(table.next_states(self.topology, &self.board))
+        } else if let Some(ref larger_than_life) = rule.larger_than_life {
+            Some(larger_than_life::next_states(
+                &rule,
+                larger_than_life,
+                self.topology,
+                &self.board,
+            ))
+        } else {
+            None
+        };
+        if let Some(states) = states {
+            self.next_board.set_states(&states);
+            mem::swap(&mut self.board, &mut self.next_board);
+            self.generation += 1;
+            return;
+        }
+
+        let board = &self.board;
+        let topology = self.topology;
+        let words_per_row = board.words_per_row();
+        let last_word_mask = board.last_word_mask();
+
+        // The state of any cell, including cells outside of the board, as a bit.
+        let cell = |x: isize, y: isize| -> u64 {
+            match topology.map(x, y, width, height) {
+                Some((x, y)) => (board.get(x, y) == CellState::Alive) as u64,
+                None => 0,
+            }
+        };
+
+        // The rows just outside of the board, determined by the topology.
+        let ghost_row = |y: isize| -> Vec<u64> {
+            let mut row = vec![0; words_per_row];
+            for x in 0..width {
+                row[x / 64] |= cell(x as isize, y) << (x % 64);
+            }
+            row
+        };
+        let above_board = ghost_row(-1);
+        let below_board = ghost_row(height as isize);
+
+        // The words of any row with the cells just outside of its left and right edge.
+        let row = |y: isize| -> (Cow<[u64]>, u64, u64) {
+            let words = if y < 0 {
+                Cow::from(&above_board[..])
+            } else if y as usize >= height {
+                Cow::from(&below_board[..])
+            } else {
+                Cow::from(board.row(y as usize))
+            };
+            (words, cell(-1, y), cell(width as isize, y))
+        };
+
+        let update_row = |y: usize, next_row: &mut [u64], next_states: &mut [u8]| {
+            let states = board.states_row(y);
+            let y = y as isize;
+            let (above, above_left, above_right) = row(y - 1);
+            let (middle, middle_left, middle_right) = row(y);
+            let (below, below_left, below_right) = row(y + 1);
+
+            for (i, next_word) in next_row.iter_mut().enumerate() {
+                let (above_l, above_r) =
+                    board::shifted_words(&above, i, width, above_left, above_right);
+                let (middle_l, middle_r) =
+                    board::shifted_words(&middle, i, width, middle_left, middle_right);
+                let (below_l, below_r) =
+                    board::shifted_words(&below, i, width, below_left, below_right);
+
+                let next = board::next_words(
+                    &rule,
+                    (above_l, above[i], above_r),
+                    (middle_l, middle[i], middle_r),
+                    (below_l, below[i], below_r),
+                );
+
+                *next_word = if states.is_empty() {
+                    next
+                } else {
+                    let cells = i * 64..width.min((i + 1) * 64);
+                    board::next_generation_states(
+                        &rule,
+                        next,
+                        middle[i],
+                        &states[cells.clone()],
+                        &mut next_states[cells],
+                    )
+                };
+            }
+
+            next_row[words_per_row - 1] &= last_word_mask;
+        };
 
-                let mut number_of_neighbours = 0;
-                for i in -1..1 + 1 {
-                    for j in -1..1 + 1 {
-                        let i: usize = (y as isize + i) as usize;
-                        let j: usize = (x as isize + j) as usize;
-                        if self.board[i][j] == CellState::Alive {
-                            number_of_neighbours += 1;
-                        }
+        let (next_words, next_states) = self.next_board.words_and_states_mut();
+        if next_states.is_empty() {
+            next_words
+                .par_chunks_mut(words_per_row)
+                .enumerate()
+                .for_each(|(y, next_row)| update_row(y, next_row, &mut []));
+        } else {
+            next_words
+                .par_chunks_mut(words_per_row)
+                .zip(next_states.par_chunks_mut(width))
+                .enumerate()
+                .for_each(|(y, (next_row, next_states))| update_row(y, next_row, next_states));
+        }
+
+        mem::swap(&mut self.board, &mut self.next_board);
+        self.generation += 1;
+    }
+}
+
+impl Automaton for GameOfLife {
+    fn step(&mut self) {
+        self.update();
+    }
+
+    fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Get the state of the cell at the given position. Cells outside of the board are dead.
+    fn get(&self, x: isize, y: isize) -> CellState {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return CellState::Dead;
+        }
+
+        self.board.get(x as usize, y as usize)
+    }
+
+    /// Set the state of the cell at the given position. Cells outside of the board are ignored.
+    fn set(&mut self, x: isize, y: isize, state: CellState) {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return;
+        }
+
+        self.board.set(x as usize, y as usize, state);
+    }
+
+    fn states(&self) -> u8 {
+        self.rule.states
+    }
+
+    fn state_color(&self, state: u8) -> Option<[u8; 3]> {
+        self.rule
+            .table
+            .as_ref()
+            .and_then(|table| table.colors.get(state as usize).cloned())
+            .and_then(|color| color)
+    }
+
+    fn population(&self) -> u64 {
+        self.board.population() as u64
+    }
+
+    fn bounds(&self) -> Option<Bounds> {
+        let mut bounds: Option<Bounds> = None;
+        for y in 0..self.height {
+            let row = self.board.row(y);
+            let first = row.iter().position(|&word| word != 0);
+            let last = row.iter().rposition(|&word| word != 0);
+            if let (Some(first), Some(last)) = (first, last) {
+                let min_x = (first * 64 + row[first].trailing_zeros() as usize) as isize;
+                let max_x = (last * 64 + 63 - row[last].leading_zeros() as usize) as isize;
+                let y = y as isize;
+
+                bounds = Some(match bounds {
+                    None => ((min_x, y), (max_x, y)),
+                    Some(((a, b), (c, _))) => ((a.min(min_x), b), (c.max(max_x), y)),
+                });
+            }
+        }
+
+        bounds
+    }
+
+    fn for_each_alive_cell(&self, region: Bounds, f: &mut dyn FnMut(isize, isize)) {
+        let ((min_x, min_y), (max_x, max_y)) = region;
+        if self.width == 0 || max_x < 0 || max_y < 0 {
+            return;
+        }
+
+        let (min_x, min_y) = (min_x.max(0) as usize, min_y.max(0) as usize);
+        let max_x = (max_x as usize).min(self.width - 1);
+        let max_y = (max_y as usize).min(self.height.saturating_sub(1));
+
+        for y in min_y..max_y + 1 {
+            if y >= self.height {
+                break;
+            }
+
+            let row = self.board.row(y);
+            for (i, &word) in row.iter().enumerate().take(max_x / 64 + 1).skip(min_x / 64) {
+                let mut word = word;
+                while word != 0 {
+                    let x = i * 64 + word.trailing_zeros() as usize;
+                    word &= word - 1;
+                    if x >= min_x && x <= max_x {
+                        f(x as isize, y as isize);
                     }
                 }
+            }
+        }
+    }
+
+    /// Counts the alive cells of a block in a row with the bits of the board's words.
+    fn block_populations(&self, region: Bounds, block: usize) -> Vec<u32> {
+        let ((min_x, min_y), (max_x, max_y)) = region;
+        let (columns, rows) = automaton::blocks(region, block);
+        let mut populations = vec![0; columns * rows];
+        if self.width == 0 || self.height == 0 {
+            return populations;
+        }
 
-                if self.board[y][x] == CellState::Alive {
-                    number_of_neighbours -= 1;
+        let max_x = max_x.min(self.width as isize - 1);
+        let max_y = max_y.min(self.height as isize - 1);
+        for y in min_y.max(0)..max_y + 1 {
+            let row = self.board.row(y as usize);
+            let populations = &mut populations[(y - min_y) as usize / block * columns..];
+            for (column, population) in populations.iter_mut().enumerate().take(columns) {
+                let start = (min_x + (column * block) as isize).max(0);
+                let end = (min_x + ((column + 1) * block) as isize - 1).min(max_x);
+                if start <= end {
+                    *population += count_alive(row, start as usize, end as usize);
                 }
+            }
+        }
 
-                *cell = number_of_neighbours
-            })
-        });
-
-        // Update cells based on their neighbour count.
-        self.board.par_iter_mut().enumerate().for_each(|(y, row)| {
-            row.par_iter_mut().enumerate().for_each(|(x, cell)| {
-                let number_of_neighbours = neighbours[y][x];
-                if *cell == CellState::Alive {
-                    if number_of_neighbours < 2 || number_of_neighbours > 3 {
-                        *cell = CellState::Dead;
-                    }
-                } else if number_of_neighbours == 3 {
-                    *cell = CellState::Alive;
+        populations
+    }
+
+    fn for_each_cell(&self, region: Bounds, f: &mut dyn FnMut(isize, isize, CellState)) {
+        if !self.board.is_multi_state() {
+            return self.for_each_alive_cell(region, &mut |x, y| f(x, y, CellState::Alive));
+        }
+
+        let ((min_x, min_y), (max_x, max_y)) = region;
+        for y in min_y.max(0)..(max_y + 1).min(self.height as isize) {
+            let states = self.board.states_row(y as usize);
+            for x in min_x.max(0)..(max_x + 1).min(self.width as isize) {
+                if states[x as usize] != 0 {
+                    f(x, y, states[x as usize].into());
                 }
-            });
-        });
+            }
+        }
+    }
+}
+
+/// The number of alive cells in a row of words, from `start` up to and including `end`.
+fn count_alive(row: &[u64], start: usize, end: usize) -> u32 {
+    let (first, last) = (start / 64, end / 64);
+    let start_mask = !0u64 << (start % 64);
+    let end_mask = !0u64 >> (63 - end % 64);
+    if first == last {
+        return (row[first] & start_mask & end_mask).count_ones();
     }
+
+    (row[first] & start_mask).count_ones()
+        + row[first + 1..last]
+            .iter()
+            .map(|word| word.count_ones())
+            .sum::<u32>()
+        + (row[last] & end_mask).count_ones()
 }
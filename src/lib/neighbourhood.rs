@@ -0,0 +1,77 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// The shape of the cells around a cell that are counted as its neighbours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Neighbourhood {
+    /// The square of cells within the range horizontally and vertically.
+    #[default]
+    Moore,
+    /// The diamond of cells whose horizontal and vertical distance add up to at most the range.
+    VonNeumann,
+    /// The disc of cells whose centres lie within the range plus half a cell.
+    Circular,
+    /// A hexagon on the square grid, which is made by leaving out the corners towards the
+    /// top-right and the bottom-left. Skewing the grid turns it into a regular hexagon.
+    Hexagonal,
+}
+
+impl Neighbourhood {
+    /// Whether the cell at the given position relative to the centre of a neighbourhood of the
+    /// given range lies in the neighbourhood.
+    #[inline]
+    pub fn contains(self, dx: isize, dy: isize, range: usize) -> bool {
+        let range = range as isize;
+        if dx.abs() > range || dy.abs() > range {
+            return false;
+        }
+
+        match self {
+            Neighbourhood::Moore => true,
+            Neighbourhood::VonNeumann => dx.abs() + dy.abs() <= range,
+            Neighbourhood::Circular => dx * dx + dy * dy <= range * range + range,
+            Neighbourhood::Hexagonal => (dx - dy).abs() <= range,
+        }
+    }
+
+    /// The number of cells in the neighbourhood, not counting its centre.
+    pub fn size(self, range: usize) -> usize {
+        let r = range as isize;
+        (-r..r + 1)
+            .flat_map(|dy| (-r..r + 1).map(move |dx| (dx, dy)))
+            .filter(|&(dx, dy)| self.contains(dx, dy, range))
+            .count()
+            - 1
+    }
+}
+
+impl FromStr for Neighbourhood {
+    type Err = String;
+
+    /// Parse the letter of a neighbourhood, as used by the `N` parameter of a Larger than Life
+    /// rule.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "M" | "m" => Ok(Neighbourhood::Moore),
+            "N" | "n" => Ok(Neighbourhood::VonNeumann),
+            "C" | "c" => Ok(Neighbourhood::Circular),
+            "H" | "h" => Ok(Neighbourhood::Hexagonal),
+            _ => Err(format!(
+                "Unknown neighbourhood `{}`, expected M (Moore), N (von Neumann), C (circular) or H (hexagonal).",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Neighbourhood {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let letter = match *self {
+            Neighbourhood::Moore => "M",
+            Neighbourhood::VonNeumann => "N",
+            Neighbourhood::Circular => "C",
+            Neighbourhood::Hexagonal => "H",
+        };
+        write!(f, "{}", letter)
+    }
+}
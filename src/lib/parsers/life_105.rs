@@ -9,15 +9,11 @@ pub fn parse_life_105_file<S: AsRef<str>>(s: &S) -> Result<Pattern, String> {
 
     let mut pattern = Pattern::default();
 
-    let metadata: String = s.lines()
-        .filter(|x| x.starts_with("#D"))
-        .map(|x| format!("{}\n", x[2..].trim()))
-        .collect();
-
-    if metadata.is_empty() {
-        pattern.description = None;
-    } else {
-        pattern.description = Some(metadata);
+    pattern.read_description_lines(s);
+
+    // The rule is normal (B3/S23) unless a `#R` line gives it in S/B notation.
+    if let Some(rule) = s.lines().find(|x| x.starts_with("#R")) {
+        pattern.rule = Some(String::from(rule[2..].trim()));
     }
 
     // Remove all lines beginning with "#", except the ones with "#P" because they give information
@@ -41,9 +37,10 @@ pub fn parse_life_105_file<S: AsRef<str>>(s: &S) -> Result<Pattern, String> {
                 None => return Err(String::from("Could not find data for x in line starting with `#P` while reading a Life 1.05 file.")),
             };
 
+            // The next line is the top row of the block.
             y = match data.next() {
-                Some(y) => match y.parse() {
-                    Ok(y) => y,
+                Some(y) => match y.parse::<isize>() {
+                    Ok(y) => y - 1,
                     Err(e) => return Err(format!("Could not read data for y: {}.", e))
                 },
                 None => return Err(String::from("Could not find data for x in line starting with `#P` while reading a Life 1.05 file.")),
@@ -68,3 +65,92 @@ pub fn parse_life_105_file<S: AsRef<str>>(s: &S) -> Result<Pattern, String> {
 
     Ok(pattern)
 }
+
+/// The widest block of cells in a Life 1.05 file that is written.
+const BLOCK_WIDTH: usize = 80;
+
+/// Formats a pattern as a Life 1.05 file, with its name, author, description and rule in `#D`,
+/// `#N` and `#R` lines. The cells are written in blocks of at most 80 columns, that each start
+/// with a `#P` line with the position of their top-left corner. Returns an error if the pattern
+/// has cells in states other than alive.
+pub fn format_life_105_file(pattern: &Pattern) -> Result<String, String> {
+    pattern.check_two_states("Life 1.05")?;
+
+    let mut s = String::from("#Life 1.05\n");
+    s.push_str(&pattern.format_description_lines());
+    s.push_str(&format_rule(pattern.rule.as_deref()));
+
+    let ((min_x, min_y), _) = pattern.extent();
+    let grid = pattern.grid();
+    let width = grid.first().map_or(0, |row| row.len());
+    let alive = |row: &[u8]| row.iter().any(|&state| state != 0);
+
+    // Every strip of columns is split in blocks of rows with alive cells.
+    for left in (0..width).step_by(BLOCK_WIDTH) {
+        let right = (left + BLOCK_WIDTH).min(width);
+        let mut y = 0;
+        while y < grid.len() {
+            if !alive(&grid[y][left..right]) {
+                y += 1;
+                continue;
+            }
+
+            let top = y;
+            while y < grid.len() && alive(&grid[y][left..right]) {
+                y += 1;
+            }
+            let rows = &grid[top..y];
+            let first = rows
+                .iter()
+                .filter_map(|row| row[left..right].iter().position(|&state| state != 0))
+                .min()
+                .unwrap_or(0)
+                + left;
+
+            s.push_str(&format!(
+                "#P {} {}\n",
+                min_x + first as isize,
+                min_y + top as isize
+            ));
+            for row in rows {
+                let end = row[..right]
+                    .iter()
+                    .rposition(|&state| state != 0)
+                    .map_or(first, |i| i + 1);
+                let line: String = row[first..end]
+                    .iter()
+                    .map(|&state| if state == 0 { '.' } else { '*' })
+                    .collect();
+                s.push_str(&line);
+                s.push('\n');
+            }
+        }
+    }
+
+    Ok(s)
+}
+
+/// The `#N` line of the normal rule, B3/S23, or the `#R` line of another rule. Rules in B/S
+/// notation are written in the S/B notation of Life 1.05, other rules are written as they are.
+fn format_rule(rule: Option<&str>) -> String {
+    let rule = match rule {
+        Some(rule) => rule.trim(),
+        None => return String::from("#N\n"),
+    };
+
+    let mut parts = rule.split('/');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(birth), Some(survival), None)
+            if birth.to_uppercase().starts_with('B')
+                && survival.to_uppercase().starts_with('S') =>
+        {
+            let (birth, survival) = (&birth[1..], &survival[1..]);
+            if birth == "3" && survival == "23" {
+                String::from("#N\n")
+            } else {
+                format!("#R {}/{}\n", survival, birth)
+            }
+        }
+        _ => format!("#R {}\n", rule),
+    }
+}
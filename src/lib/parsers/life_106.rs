@@ -7,12 +7,13 @@ pub fn is_life_106_file<S: AsRef<str>>(s: &S) -> bool {
 pub fn parse_life_106_file<S: AsRef<str>>(s: &S) -> Result<Pattern, String> {
     let s = s.as_ref();
 
-    // Skip first line, because it is the header.
-    let lines = s.lines().skip(1);
-
     let mut pattern = Pattern::default();
+    pattern.read_description_lines(s);
 
-    for line in lines.filter(|s| !s.is_empty()) {
+    // Skip first line, because it is the header, and the `#D` lines, that have been read above.
+    let lines = s.lines().skip(1);
+
+    for line in lines.filter(|s| !s.is_empty() && !s.starts_with('#')) {
         let mut line_split = line.split_whitespace();
 
         let x = match line_split.next() {
@@ -38,3 +39,18 @@ pub fn parse_life_106_file<S: AsRef<str>>(s: &S) -> Result<Pattern, String> {
 
     Ok(pattern)
 }
+
+/// Formats a pattern as a Life 1.06 file, which lists the position of every alive cell after
+/// `#D` lines with its name, author and description. The rule is not written, because Life 1.06
+/// has no line for it. Returns an error if the pattern has cells in states other than alive.
+pub fn format_life_106_file(pattern: &Pattern) -> Result<String, String> {
+    pattern.check_two_states("Life 1.06")?;
+
+    let mut s = String::from("#Life 1.06\n");
+    s.push_str(&pattern.format_description_lines());
+    for &(x, y) in &pattern.cells {
+        s.push_str(&format!("{} {}\n", x, y));
+    }
+
+    Ok(s)
+}
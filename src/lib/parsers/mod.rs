@@ -1,5 +1,7 @@
+use super::automaton::Automaton;
+use super::{Bounds, CellState};
 use std::fs::File;
-use std::io::Read;
+use std::io::{Read, Write};
 
 pub mod life_105;
 pub mod life_106;
@@ -29,17 +31,35 @@ impl FileType {
     }
 }
 
-#[derive(Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Pattern {
     pub cells: Vec<(isize, isize)>,
+    /// The states of the cells of a multi-state pattern, in the same order as the cells.
+    /// This is empty if all cells are alive.
+    pub states: Vec<u8>,
     pub name: Option<String>,
     pub description: Option<String>,
     pub author: Option<String>,
+    /// The width of the pattern, as declared by the file.
+    pub width: Option<usize>,
+    /// The height of the pattern, as declared by the file.
+    pub height: Option<usize>,
+    /// The rule of the pattern, as declared by the file.
+    pub rule: Option<String>,
 }
 
 impl Pattern {
     pub fn from_file<S: AsRef<str>>(filename: S) -> Result<Pattern, String> {
         let filename = filename.as_ref();
+        let file_type = match FileType::from_filename(&filename) {
+            Some(file_type) => file_type,
+            None => {
+                return Err(format!(
+                    "Unrecognised file type of `{}`, expected a .rle, .cells, .lif or .life file.",
+                    filename
+                ))
+            }
+        };
 
         // Read file and get rules from them.
         let mut file = match File::open(filename) {
@@ -52,9 +72,6 @@ impl Pattern {
             return Err(format!("Could not read file to string: {}", e));
         }
 
-        let file_type: FileType =
-            FileType::from_filename(&filename).expect("Unrecognised file type.");
-
         match file_type {
             FileType::Life => {
                 if life_106::is_life_106_file(&contents) {
@@ -75,4 +92,188 @@ impl Pattern {
             FileType::RLE => rle::parse_rle_file(&contents),
         }
     }
+
+    /// Write the pattern to a file, in the format that belongs to the extension of the file name.
+    /// `.life` files are written in the Life 1.05 format and `.lif` files in the Life 1.06 format.
+    pub fn to_file<S: AsRef<str>>(&self, filename: S) -> Result<(), String> {
+        let filename = filename.as_ref();
+        let contents = match FileType::from_filename(&filename) {
+            Some(FileType::Life) if filename.ends_with("life") => {
+                life_105::format_life_105_file(self)?
+            }
+            Some(FileType::Life) => life_106::format_life_106_file(self)?,
+            Some(FileType::PlainText) => plaintext::format_plaintext_file(self)?,
+            Some(FileType::RLE) => rle::format_rle_file(self),
+            None => {
+                return Err(format!(
+                    "Unrecognised file type of `{}`, expected a .rle, .cells, .lif or .life file.",
+                    filename
+                ))
+            }
+        };
+
+        let mut file = match File::create(filename) {
+            Ok(f) => f,
+            Err(e) => return Err(format!("Could not create file: {}", e)),
+        };
+        file.write_all(contents.as_bytes())
+            .map_err(|e| format!("Could not write file: {}", e))
+    }
+
+    /// Copy the cells of a region of an automaton, at their positions relative to the top-left
+    /// corner of the region. The width and height of the pattern are those of the region.
+    pub fn from_automaton(automaton: &dyn Automaton, region: Bounds) -> Pattern {
+        let ((min_x, min_y), (max_x, max_y)) = region;
+        let mut pattern = Pattern {
+            width: Some((max_x - min_x + 1).max(0) as usize),
+            height: Some((max_y - min_y + 1).max(0) as usize),
+            ..Pattern::default()
+        };
+
+        automaton.for_each_cell(region, &mut |x, y, state| {
+            pattern.cells.push((x - min_x, y - min_y));
+            pattern.states.push(state.into());
+        });
+        if pattern.states.iter().all(|&state| state == 1) {
+            pattern.states.clear();
+        }
+
+        pattern
+    }
+
+    /// The state of the cell at the given index of `cells`.
+    pub fn state(&self, index: usize) -> CellState {
+        self.states
+            .get(index)
+            .map_or(CellState::Alive, |&state| state.into())
+    }
+
+    /// The width and height of the pattern, as declared or else as needed for its cells.
+    pub fn size(&self) -> (usize, usize) {
+        let max_x = self.cells.iter().map(|&(x, _)| x + 1).max().unwrap_or(0);
+        let max_y = self.cells.iter().map(|&(_, y)| y + 1).max().unwrap_or(0);
+
+        (
+            self.width.unwrap_or(max_x.max(0) as usize),
+            self.height.unwrap_or(max_y.max(0) as usize),
+        )
+    }
+
+    /// Rotate the pattern clockwise by 90 degrees, within its width and height.
+    pub fn rotate_clockwise(&mut self) {
+        let (width, height) = self.size();
+        for cell in &mut self.cells {
+            *cell = (height as isize - 1 - cell.1, cell.0);
+        }
+        self.width = Some(height);
+        self.height = Some(width);
+    }
+
+    /// Mirror the pattern from left to right, within its width.
+    pub fn flip_horizontally(&mut self) {
+        let (width, _) = self.size();
+        for cell in &mut self.cells {
+            cell.0 = width as isize - 1 - cell.0;
+        }
+    }
+
+    /// Mirror the pattern from top to bottom, within its height.
+    pub fn flip_vertically(&mut self) {
+        let (_, height) = self.size();
+        for cell in &mut self.cells {
+            cell.1 = height as isize - 1 - cell.1;
+        }
+    }
+
+    /// Set the cells of the pattern in an automaton, with the top-left corner of the pattern at
+    /// the given position. The cells that are dead in the pattern are left as they are.
+    pub fn paste(&self, automaton: &mut dyn Automaton, origin: (isize, isize)) {
+        for (i, &(x, y)) in self.cells.iter().enumerate() {
+            automaton.set(origin.0 + x, origin.1 + y, self.state(i));
+        }
+    }
+
+    /// The rectangle that holds the cells of the pattern and, if they are declared, its width and
+    /// height from the position (0, 0).
+    fn extent(&self) -> Bounds {
+        let declared = match (self.width, self.height) {
+            (Some(width), Some(height)) => {
+                Some(((0, 0), (width as isize - 1, height as isize - 1)))
+            }
+            _ => None,
+        };
+
+        self.cells
+            .iter()
+            .fold(declared, |extent, &(x, y)| match extent {
+                Some(((min_x, min_y), (max_x, max_y))) => {
+                    Some(((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y))))
+                }
+                None => Some(((x, y), (x, y))),
+            })
+            .unwrap_or(((0, 0), (-1, -1)))
+    }
+
+    /// The state of every cell in the extent of the pattern, row after row.
+    fn grid(&self) -> Vec<Vec<u8>> {
+        let ((min_x, min_y), (max_x, max_y)) = self.extent();
+        let width = (max_x - min_x + 1).max(0) as usize;
+        let height = (max_y - min_y + 1).max(0) as usize;
+
+        let mut grid = vec![vec![0; width]; height];
+        for (i, &(x, y)) in self.cells.iter().enumerate() {
+            grid[(y - min_y) as usize][(x - min_x) as usize] = self.state(i).into();
+        }
+        grid
+    }
+
+    /// Reads the name and author from the `#D Name:` and `#D Author:` lines of a Life 1.05 or 1.06
+    /// file, and the description from its other `#D` lines.
+    fn read_description_lines(&mut self, s: &str) {
+        let mut description = Vec::new();
+        for line in s.lines().filter(|x| x.starts_with("#D")) {
+            let line = line[2..].trim();
+            if let Some(name) = line.strip_prefix("Name:") {
+                self.name = Some(String::from(name.trim()));
+            } else if let Some(author) = line.strip_prefix("Author:") {
+                self.author = Some(String::from(author.trim()));
+            } else {
+                description.push(line);
+            }
+        }
+
+        if !description.is_empty() {
+            self.description = Some(description.join("\n"));
+        }
+    }
+
+    /// The `#D` lines with the name, author and description of a Life 1.05 or 1.06 file.
+    fn format_description_lines(&self) -> String {
+        let mut s = String::new();
+        if let Some(ref name) = self.name {
+            s.push_str(&format!("#D Name: {}\n", name));
+        }
+        if let Some(ref author) = self.author {
+            s.push_str(&format!("#D Author: {}\n", author));
+        }
+        if let Some(ref description) = self.description {
+            for line in description.lines() {
+                s.push_str(&format!("#D {}\n", line));
+            }
+        }
+        s
+    }
+
+    /// Returns an error if the pattern has cells that are not alive, for formats that can only
+    /// store alive cells.
+    fn check_two_states(&self, format: &str) -> Result<(), String> {
+        if self.states.iter().any(|&state| state != 1) {
+            Err(format!(
+                "The {} format can only store alive cells, but this pattern has cells in other states.",
+                format
+            ))
+        } else {
+            Ok(())
+        }
+    }
 }
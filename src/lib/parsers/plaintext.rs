@@ -21,8 +21,8 @@ pub fn parse_plaintext_file<S: AsRef<str>>(s: &S) -> Result<Pattern, String> {
     // Process comments (lines starting with '!')
     for description in metadata {
         // Check for other information
-        if description.starts_with("!Author:") {
-            let description = description[8..].trim();
+        if let Some(description) = description.strip_prefix("!Author:") {
+            let description = description.trim();
             pattern.author = Some(String::from(description));
         } else {
             // Default, this line is a description
@@ -56,3 +56,37 @@ pub fn parse_plaintext_file<S: AsRef<str>>(s: &S) -> Result<Pattern, String> {
 
     Ok(pattern)
 }
+
+/// Formats a pattern as a plaintext file, with its name, author and description. Dead cells at
+/// the end of a row are left out. Returns an error if the pattern has cells in states other than
+/// alive.
+pub fn format_plaintext_file(pattern: &Pattern) -> Result<String, String> {
+    pattern.check_two_states("plaintext")?;
+
+    let mut s = format!(
+        "!Name: {}\n",
+        pattern.name.as_ref().map_or("", String::as_str)
+    );
+    if let Some(ref author) = pattern.author {
+        s.push_str(&format!("!Author: {}\n", author));
+    }
+    if let Some(ref description) = pattern.description {
+        for line in description.lines() {
+            s.push_str(&format!("!{}\n", line));
+        }
+    }
+    for row in pattern.grid() {
+        let end = row
+            .iter()
+            .rposition(|&state| state != 0)
+            .map_or(0, |i| i + 1);
+        let line: String = row[..end]
+            .iter()
+            .map(|&state| if state == 0 { '.' } else { 'O' })
+            .collect();
+        s.push_str(&line);
+        s.push('\n');
+    }
+
+    Ok(s)
+}
@@ -47,60 +47,80 @@ pub fn parse_rle_file<S: ToString>(s: &S) -> Result<Pattern, String> {
     // Remove all of the lines starting with `#`
     let mut lines = s.lines().skip_while(|x| x.starts_with('#'));
 
-    // x = m, y = n
-    let _header = match lines.next() {
+    // x = m, y = n, rule = abc
+    let header = match lines.next() {
         Some(v) => v,
         None => return Err(String::from("The header for this `.rle` file could not be found because there were no (uncommented) lines.")),
     };
+    let (width, height, rule) = parse_rle_header(&header)?;
+    pattern.width = Some(width);
+    pattern.height = Some(height);
+    pattern.rule = rule;
 
-    // TODO: process header information
+    let (width, height) = (width as isize, height as isize);
+
+    // Adds cells to the pattern, if they lie within the bounds declared in the header.
+    macro_rules! push_cells {
+        ($x:expr, $y:expr, $amount:expr, $state:expr) => {{
+            let (x, y) = ($x, $y);
+            if x + $amount > width || y >= height {
+                return Err(format!(
+                    "The cell at ({}, {}) lies outside of the bounds declared in the header of this `.rle` file (x = {}, y = {}).",
+                    x + $amount - 1, y, width, height
+                ));
+            }
+            for i in 0..$amount {
+                pattern.cells.push((x + i, y));
+                pattern.states.push($state);
+            }
+        }};
+    }
 
     let data: String = lines.collect();
     let data = data.split('$');
 
     let mut y: isize = 0;
-    for line in data {
+    'lines: for line in data {
         let mut amount: isize = 0;
         let mut x = 0;
+        // The prefix `p` to `y` of a state above 24 in a multi-state pattern.
+        let mut prefix: Option<u8> = None;
         for c in line.chars() {
+            // The number of cells in this run, which is 1 if no number precedes it.
+            let run = if amount == 0 { 1 } else { amount };
             match c {
                 'b' | '.' => {
                     // Off state
-                    if amount == 0 {
-                        // Not preceded by a number
-                        x += 1;
-                    } else {
-                        x += amount;
-                        amount = 0;
-                    }
+                    x += run;
+                    amount = 0;
                 }
-                'o' | 'A' => {
+                'o' => {
                     // On state
-                    if amount == 0 {
-                        // Not preceded by a number
-                        pattern.cells.push((x, y));
-                        x += 1;
-                    } else {
-                        for i in 0..amount {
-                            pattern.cells.push((x + i, y));
-                        }
-                        x += amount;
-                        amount = 0;
+                    push_cells!(x, y, run, 1);
+                    x += run;
+                    amount = 0;
+                }
+                'A'..='X' => {
+                    // State 1 to 24, or a higher state if preceded by a prefix
+                    let state =
+                        prefix.take().unwrap_or(0) as usize * 24 + (c as u8 - b'A') as usize + 1;
+                    if state > 255 {
+                        return Err(format!(
+                            "The state {} in this `.rle` file is higher than the maximum of 255.",
+                            state
+                        ));
                     }
+                    push_cells!(x, y, run, state as u8);
+                    x += run;
+                    amount = 0;
                 }
-                '0' => amount *= 10,
-                '1' => amount = amount * 10 + 1,
-                '2' => amount = amount * 10 + 2,
-                '3' => amount = amount * 10 + 3,
-                '4' => amount = amount * 10 + 4,
-                '5' => amount = amount * 10 + 5,
-                '6' => amount = amount * 10 + 6,
-                '7' => amount = amount * 10 + 7,
-                '8' => amount = amount * 10 + 8,
-                '9' => amount = amount * 10 + 9,
+                'p'..='y' if prefix.is_none() => {
+                    prefix = Some(c as u8 - b'p' + 1);
+                }
+                '0'..='9' => amount = amount * 10 + c.to_digit(10).unwrap() as isize,
                 '!' => {
                     // The end of this pattern was reached
-                    return Ok(pattern);
+                    break 'lines;
                 }
                 unknown => {
                     return Err(format!(
@@ -117,5 +137,178 @@ pub fn parse_rle_file<S: ToString>(s: &S) -> Result<Pattern, String> {
         }
     }
 
+    // Two-state patterns don't keep the state of every cell.
+    if pattern.states.iter().all(|&state| state == 1) {
+        pattern.states.clear();
+    }
+
     Ok(pattern)
 }
+
+/// Parses the header line of a `.rle` file, like `x = 3, y = 3, rule = B3/S23`, and returns the
+/// declared width, height and rule.
+/// A bounded grid that is given after the rule, like `:T20,20` in `B3/S23:T20,20`, is left out,
+/// because the topology of the board is not read from the file.
+pub fn parse_rle_header<S: AsRef<str>>(s: &S) -> Result<(usize, usize, Option<String>), String> {
+    let s = s.as_ref();
+
+    let mut width = None;
+    let mut height = None;
+    let mut rule = None;
+
+    let mut rest = Some(s);
+    while let Some(fields) = rest {
+        let mut field = fields.splitn(2, '=');
+        let (key, value) = match (field.next(), field.next()) {
+            (Some(key), Some(value)) => (key.trim(), value),
+            _ => {
+                return Err(format!(
+                    "Malformed header `{}` in `.rle` file, expected fields like `x = m`.",
+                    s
+                ))
+            }
+        };
+
+        // The rule is the last field and may itself contain commas, like `R2,C0,M1,S2..3,B3..3`.
+        if key == "rule" {
+            let value = value.split(':').next().unwrap_or("");
+            rule = Some(String::from(value.trim()));
+            break;
+        }
+
+        let mut value = value.splitn(2, ',');
+        let (value, next) = (value.next().unwrap_or("").trim(), value.next());
+        rest = next;
+
+        let value: usize = match value.parse() {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(format!(
+                    "Could not read `{}` in header of `.rle` file: {}.",
+                    key, e
+                ))
+            }
+        };
+
+        match key {
+            "x" => width = Some(value),
+            "y" => height = Some(value),
+            unknown => {
+                return Err(format!(
+                    "Unknown field `{}` in header of `.rle` file, expected `x`, `y` or `rule`.",
+                    unknown
+                ))
+            }
+        }
+    }
+
+    match (width, height) {
+        (Some(width), Some(height)) => Ok((width, height, rule)),
+        _ => Err(format!(
+            "Malformed header `{}` in `.rle` file, both `x` and `y` should be given.",
+            s
+        )),
+    }
+}
+
+/// The longest line of cells in a `.rle` file that is written.
+const LINE_LENGTH: usize = 70;
+
+/// Formats a pattern as a `.rle` file, with its name, author, description and rule. Runs of cells
+/// in the same state are compressed, and the lines with cells are at most 70 characters long.
+pub fn format_rle_file(pattern: &Pattern) -> String {
+    let grid = pattern.grid();
+    let width = grid.first().map_or(0, |row| row.len());
+    let multi_state = !pattern.states.is_empty();
+
+    let mut s = String::new();
+    if let Some(ref name) = pattern.name {
+        s.push_str(&format!("#N {}\n", name));
+    }
+    if let Some(ref author) = pattern.author {
+        s.push_str(&format!("#O {}\n", author));
+    }
+    if let Some(ref description) = pattern.description {
+        for line in description.lines() {
+            s.push_str(&format!("#C {}\n", line));
+        }
+    }
+    s.push_str(&format!("x = {}, y = {}", width, grid.len()));
+    if let Some(ref rule) = pattern.rule {
+        s.push_str(&format!(", rule = {}", rule));
+    }
+    s.push('\n');
+
+    // The runs of cells, which are not split over lines.
+    let mut runs = Vec::new();
+    // The number of ends of rows that have not been written yet.
+    let mut row_ends = 0;
+    for row in &grid {
+        // Dead cells at the end of a row are left out.
+        let end = row
+            .iter()
+            .rposition(|&state| state != 0)
+            .map_or(0, |i| i + 1);
+        if end > 0 {
+            if row_ends > 0 {
+                runs.push(format_run(row_ends, "$"));
+                row_ends = 0;
+            }
+
+            let mut start = 0;
+            while start < end {
+                let state = row[start];
+                let length = row[start..end]
+                    .iter()
+                    .take_while(|&&other| other == state)
+                    .count();
+                runs.push(format_run(length, &format_state(state, multi_state)));
+                start += length;
+            }
+        }
+        row_ends += 1;
+    }
+    runs.push(String::from("!"));
+
+    let mut line = String::new();
+    for run in runs {
+        if line.len() + run.len() > LINE_LENGTH {
+            s.push_str(&line);
+            s.push('\n');
+            line.clear();
+        }
+        line.push_str(&run);
+    }
+    s.push_str(&line);
+    s.push('\n');
+
+    s
+}
+
+/// A run of cells in the same state, or of ends of rows, like `3o` or `$`.
+fn format_run(length: usize, tag: &str) -> String {
+    if length == 1 {
+        String::from(tag)
+    } else {
+        format!("{}{}", length, tag)
+    }
+}
+
+/// The tag of a state: `b` and `o` in two-state patterns, and `.`, `A` to `X` or a prefix `p` to
+/// `y` followed by `A` to `X` in multi-state patterns.
+fn format_state(state: u8, multi_state: bool) -> String {
+    match (state, multi_state) {
+        (0, false) => String::from("b"),
+        (_, false) => String::from("o"),
+        (0, true) => String::from("."),
+        (state, true) => {
+            let (prefix, letter) = ((state - 1) / 24, (state - 1) % 24);
+            let letter = char::from(b'A' + letter);
+            if prefix == 0 {
+                letter.to_string()
+            } else {
+                format!("{}{}", char::from(b'p' + prefix - 1), letter)
+            }
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use super::board::Board;
+use super::GameOfLife;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// The generations of a game of life that have been seen, to find out when a board repeats
+/// because it has become a still life or an oscillator.
+/// Only the hash of every board is kept, with a copy of the first board that was checked. When a
+/// hash has been seen before, the generations since the first board are computed again to
+/// compare the boards cell by cell, so that two different boards with the same hash are not taken
+/// for a repetition.
+#[derive(Default)]
+pub struct Periods {
+    /// The generations with each hash.
+    seen: HashMap<u64, Vec<u64>>,
+    /// The game of life at the first generation that was checked.
+    start: Option<GameOfLife>,
+}
+
+impl Periods {
+    pub fn new() -> Periods {
+        Periods::default()
+    }
+
+    /// Remember the board of the current generation of a game of life. Return the earlier
+    /// generation with the same board, if there is one.
+    /// The generations should be checked one after the other.
+    pub fn check(&mut self, game_of_life: &GameOfLife) -> Option<u64> {
+        let start = self.start.get_or_insert_with(|| {
+            let mut start = GameOfLife::new(0, 0);
+            start.copy_from(game_of_life);
+            start
+        });
+
+        let mut hasher = DefaultHasher::new();
+        game_of_life.board.hash(&mut hasher);
+        let generations = self.seen.entry(hasher.finish()).or_default();
+
+        let previous = generations
+            .iter()
+            .cloned()
+            .find(|&previous| board_at(start, previous) == game_of_life.board);
+        if previous.is_none() {
+            generations.push(game_of_life.generation);
+        }
+        previous
+    }
+}
+
+/// The board of a later generation of a game of life, which is left unchanged.
+fn board_at(game_of_life: &GameOfLife, generation: u64) -> Board {
+    let mut copy = GameOfLife::new(0, 0);
+    copy.copy_from(game_of_life);
+    while copy.generation < generation {
+        copy.update();
+    }
+    copy.board
+}
@@ -0,0 +1,295 @@
+use super::isotropic::{Isotropic, NEIGHBOURS};
+use super::larger_than_life::LargerThanLife;
+use super::neighbourhood::Neighbourhood;
+use super::rule_table::RuleTable;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// An outer-totalistic rule for a Life-like cellular automaton.
+///
+/// `birth[n]` is true if a dead cell with `n` alive neighbours becomes alive, and `survival[n]`
+/// is true if an alive cell with `n` alive neighbours stays alive. For isotropic non-totalistic
+/// rules, this is true if it happens for some arrangement of `n` alive neighbours.
+///
+/// Rules with more than two states are Generations rules: an alive cell that does not survive
+/// passes through the dying states `2` to `states - 1` before it is dead, and dying cells can
+/// neither be born nor count as alive neighbours.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub birth: [bool; 9],
+    pub survival: [bool; 9],
+    /// The number of states a cell can be in, including the alive and dead states.
+    pub states: u8,
+    /// The neighbourhood and neighbour counts of a Larger than Life rule, which are used instead
+    /// of `birth` and `survival`.
+    pub larger_than_life: Option<LargerThanLife>,
+    /// The arrangements of alive neighbours for which cells are born or survive, for rules in
+    /// which this does not only depend on the number of alive neighbours.
+    pub isotropic: Option<Isotropic>,
+    /// The cells around a cell that are counted as its neighbours: a Moore, von Neumann or
+    /// hexagonal neighbourhood of range 1. Larger than Life rules have their own neighbourhood.
+    pub neighbourhood: Neighbourhood,
+    /// The table of transitions of a rule that is given as a rule table, like Wireworld, which
+    /// is used instead of all other fields except `states`.
+    pub table: Option<Arc<RuleTable>>,
+}
+
+impl Rule {
+    /// Return a new rule from the neighbour counts for which a cell is born or survives.
+    pub fn new(birth: &[usize], survival: &[usize]) -> Rule {
+        let mut rule = Rule {
+            birth: [false; 9],
+            survival: [false; 9],
+            states: 2,
+            larger_than_life: None,
+            isotropic: None,
+            neighbourhood: Neighbourhood::Moore,
+            table: None,
+        };
+
+        for &n in birth.iter().filter(|&&n| n <= 8) {
+            rule.birth[n] = true;
+        }
+        for &n in survival.iter().filter(|&&n| n <= 8) {
+            rule.survival[n] = true;
+        }
+
+        rule
+    }
+
+    /// Conway's Game of Life: B3/S23.
+    pub fn conway() -> Rule {
+        Rule::new(&[3], &[2, 3])
+    }
+
+    /// Return a new Generations rule, in which cells that die pass through `states - 2` dying
+    /// states.
+    pub fn generations(birth: &[usize], survival: &[usize], states: u8) -> Rule {
+        Rule {
+            states,
+            ..Rule::new(birth, survival)
+        }
+    }
+
+    /// Whether a cell is alive in the next generation, given whether it is alive now and its
+    /// arrangement of alive neighbours, as described by `isotropic::NEIGHBOURS`. Neighbours that
+    /// lie outside of the rule's neighbourhood are ignored.
+    /// This ignores dying states and Larger than Life neighbourhoods.
+    pub fn next_cell(&self, alive: bool, neighbours: u8) -> bool {
+        let neighbours = NEIGHBOURS
+            .iter()
+            .enumerate()
+            .filter(|&(_, &(dx, dy))| self.neighbourhood.contains(dx, dy, 1))
+            .fold(0, |mask, (i, _)| mask | 1 << i)
+            & neighbours;
+        match (self.isotropic, alive) {
+            (Some(isotropic), true) => isotropic.survives(neighbours),
+            (Some(isotropic), false) => isotropic.births(neighbours),
+            (None, true) => self.survival[neighbours.count_ones() as usize],
+            (None, false) => self.birth[neighbours.count_ones() as usize],
+        }
+    }
+
+    /// Return a new Larger than Life rule with the given number of states.
+    pub fn larger_than_life(larger_than_life: LargerThanLife, states: u8) -> Rule {
+        Rule {
+            states,
+            larger_than_life: Some(larger_than_life),
+            ..Rule::new(&[], &[])
+        }
+    }
+
+    /// Return a new rule that is given as a rule table.
+    pub fn table(table: RuleTable) -> Rule {
+        Rule {
+            states: table.states,
+            table: Some(Arc::new(table)),
+            ..Rule::new(&[], &[])
+        }
+    }
+
+    /// Wireworld, which is a built-in rule table.
+    pub fn wireworld() -> Rule {
+        Rule::table(RuleTable::builtin("WireWorld").unwrap())
+    }
+
+    /// Brian's Brain: B2/S/C3.
+    pub fn brians_brain() -> Rule {
+        Rule::generations(&[2], &[], 3)
+    }
+
+    /// Parse the number of states of a Generations rule, like the `C3` in `B2/S/C3` or the `4` in
+    /// `345/2/4`.
+    fn parse_states(s: &str) -> Result<u8, String> {
+        let number = s.strip_prefix(['C', 'c']).unwrap_or(s);
+
+        match number.parse::<u8>() {
+            Ok(states) if states >= 2 => Ok(states),
+            _ => Err(format!(
+                "Could not parse the number of states `{}`, expected a number from 2 to 255.",
+                s
+            )),
+        }
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Rule::conway()
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    /// Parse a rule in B/S notation (`B36/S23`) or in S/B notation (`23/36`).
+    /// Generations rules have the number of states as a third part, like `B2/S/C3` or `/2/3`.
+    /// Isotropic non-totalistic rules are parsed in Hensel notation, like `B3-cnqy/S23-a`.
+    /// Rules ending in `H` or `V` use a hexagonal or von Neumann neighbourhood, like `B2/S34H`.
+    /// Larger than Life rules are parsed in Golly's notation, like `R5,C0,M1,S34..58,B34..45,NM`.
+    /// Rule tables are given by the name of a built-in rule table, like `WireWorld`, or by the
+    /// name of a `.rule` file.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if let Some(table) = RuleTable::builtin(s) {
+            return Ok(Rule::table(table));
+        }
+        if s.ends_with(".rule") {
+            return RuleTable::from_file(s).map(Rule::table);
+        }
+
+        if s.starts_with(['R', 'r']) && s.contains(',') {
+            let (larger_than_life, states) = LargerThanLife::parse(s)?;
+            return Ok(Rule::larger_than_life(larger_than_life, states));
+        }
+
+        let (s, neighbourhood) = match s.chars().last() {
+            Some('H') | Some('h') => (&s[..s.len() - 1], Neighbourhood::Hexagonal),
+            Some('V') | Some('v') => (&s[..s.len() - 1], Neighbourhood::VonNeumann),
+            _ => (s, Neighbourhood::Moore),
+        };
+
+        let parts: Vec<&str> = s.split('/').map(str::trim).collect();
+        let (first, second, states) = match parts[..] {
+            [first, second] => (first, second, 2),
+            [first, second, states] => (first, second, Rule::parse_states(states)?),
+            _ => {
+                return Err(format!(
+                    "Could not parse rule `{}`: expected two or three parts separated by `/`.",
+                    s
+                ))
+            }
+        };
+
+        let first_prefix = first.chars().next().map(|c| c.to_ascii_uppercase());
+        let second_prefix = second.chars().next().map(|c| c.to_ascii_uppercase());
+
+        let (birth, survival) = match (first_prefix, second_prefix) {
+            (Some('B'), Some('S')) => (&first[1..], &second[1..]),
+            (Some('S'), Some('B')) => (&second[1..], &first[1..]),
+            // S/B notation without letters, like `23/3`.
+            _ if !first.starts_with(char::is_alphabetic)
+                && !second.starts_with(char::is_alphabetic) =>
+            {
+                (second, first)
+            }
+            _ => {
+                return Err(format!(
+                    "Could not parse rule `{}`: expected B/S notation (like `B3/S23`) or S/B notation (like `23/3`).",
+                    s
+                ))
+            }
+        };
+
+        let isotropic = Isotropic::parse(birth, survival)?;
+        if neighbourhood != Neighbourhood::Moore {
+            if !isotropic.is_totalistic() {
+                return Err(format!(
+                    "Could not parse rule `{}`: Hensel notation can only be used with the Moore neighbourhood.",
+                    s
+                ));
+            }
+
+            let size = neighbourhood.size(1);
+            let counts = isotropic.birth_counts();
+            let too_many = (size + 1..9).any(|n| counts[n] || isotropic.survival_counts()[n]);
+            if too_many {
+                return Err(format!(
+                    "Could not parse rule `{}`: cells only have {} neighbours in this neighbourhood.",
+                    s, size
+                ));
+            }
+        }
+
+        Ok(Rule {
+            birth: isotropic.birth_counts(),
+            survival: isotropic.survival_counts(),
+            states,
+            larger_than_life: None,
+            isotropic: if isotropic.is_totalistic() {
+                None
+            } else {
+                Some(isotropic)
+            },
+            neighbourhood,
+            table: None,
+        })
+    }
+}
+
+impl fmt::Display for Rule {
+    /// Format the rule in B/S notation, like `B3/S23`, or `B2/S/C3` for Generations rules, with a
+    /// suffix for hexagonal and von Neumann neighbourhoods.
+    /// Larger than Life rules are formatted in Golly's notation, and rule tables by their name.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref table) = self.table {
+            return write!(f, "{}", table.name);
+        }
+        if let Some(ref ltl) = self.larger_than_life {
+            let states = if self.states > 2 { self.states } else { 0 };
+            return write!(
+                f,
+                "R{},C{},M{},S{}..{},B{}..{},N{}",
+                ltl.range,
+                states,
+                ltl.middle as u8,
+                ltl.survival.0,
+                ltl.survival.1,
+                ltl.birth.0,
+                ltl.birth.1,
+                ltl.neighbourhood
+            );
+        }
+
+        write!(f, "B")?;
+        match self.isotropic {
+            Some(ref isotropic) => isotropic.fmt_birth(f)?,
+            None => {
+                for n in (0..9).filter(|&n| self.birth[n]) {
+                    write!(f, "{}", n)?;
+                }
+            }
+        }
+        write!(f, "/S")?;
+        match self.isotropic {
+            Some(ref isotropic) => isotropic.fmt_survival(f)?,
+            None => {
+                for n in (0..9).filter(|&n| self.survival[n]) {
+                    write!(f, "{}", n)?;
+                }
+            }
+        }
+        if self.states > 2 {
+            write!(f, "/C{}", self.states)?;
+        }
+        match self.neighbourhood {
+            Neighbourhood::Hexagonal => write!(f, "H")?,
+            Neighbourhood::VonNeumann => write!(f, "V")?,
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
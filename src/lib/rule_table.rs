@@ -0,0 +1,553 @@
+use super::board::Board;
+use super::neighbourhood::Neighbourhood;
+use super::rayon::prelude::*;
+use super::topology::Topology;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::Read;
+
+/// Wireworld, which models electronic circuits: electron heads (1) become electron tails (2),
+/// electron tails become conductors (3), and conductors become electron heads if one or two of
+/// their neighbours are electron heads.
+const WIREWORLD: &str = "@RULE WireWorld
+
+@TABLE
+n_states:4
+neighborhood:Moore
+symmetries:permute
+
+var a={0,1,2,3}
+var b={0,1,2,3}
+var c={0,1,2,3}
+var d={0,1,2,3}
+var e={0,1,2,3}
+var f={0,1,2,3}
+var g={0,1,2,3}
+var h={0,1,2,3}
+var i={0,2,3}
+var j={0,2,3}
+var k={0,2,3}
+var l={0,2,3}
+var m={0,2,3}
+var n={0,2,3}
+var o={0,2,3}
+
+# Electron heads become tails and tails become conductors.
+1,a,b,c,d,e,f,g,h,2
+2,a,b,c,d,e,f,g,h,3
+# Conductors with one or two electron heads as neighbours become electron heads.
+3,1,i,j,k,l,m,n,o,1
+3,1,1,i,j,k,l,m,n,1
+
+@COLORS
+0 48 48 48
+1 0 128 255
+2 255 255 255
+3 255 128 0
+";
+
+/// The rule tables that are always available, by name.
+const BUILTIN: [&str; 1] = [WIREWORLD];
+
+/// A set of states, with one bit for each of the 256 possible states.
+type States = [u64; 4];
+
+fn contains(states: &States, state: u8) -> bool {
+    states[state as usize / 64] >> (state % 64) & 1 == 1
+}
+
+fn single(state: u8) -> States {
+    let mut states = [0; 4];
+    states[state as usize / 64] |= 1 << (state % 64);
+    states
+}
+
+/// A rule that is given as a table of transitions, in the format of the `@TABLE` section of
+/// Golly's `.rule` files.
+///
+/// Every transition lists the states of a cell and its neighbours, or variables that stand for
+/// sets of states, and the state the cell changes to. The first transition that matches a cell
+/// is used, and cells that match no transition keep their state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleTable {
+    pub name: String,
+    /// The number of states a cell can be in.
+    pub states: u8,
+    /// The neighbourhood of range 1 that is used.
+    pub neighbourhood: Neighbourhood,
+    /// The colors of the states, as declared by the `@COLORS` section.
+    pub colors: Vec<Option<[u8; 3]>>,
+    /// The number of words that are needed for one bit per transition.
+    words: usize,
+    /// The transitions whose input at a position accepts a state: bit `t % 64` of word
+    /// `(position * states + state) * words + t / 64` is set if transition `t` does.
+    inputs: Vec<u64>,
+    /// The state that each transition changes a cell to.
+    outputs: Vec<u8>,
+}
+
+impl RuleTable {
+    /// Return the built-in rule table with the given name, ignoring case, like `WireWorld`.
+    pub fn builtin(name: &str) -> Option<RuleTable> {
+        BUILTIN
+            .iter()
+            .map(|table| RuleTable::parse(table).expect("Built-in rule tables are valid."))
+            .find(|table| table.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Read a rule table from a `.rule` file.
+    pub fn from_file<S: AsRef<str>>(filename: S) -> Result<RuleTable, String> {
+        let mut contents = String::new();
+        File::open(filename.as_ref())
+            .and_then(|mut file| file.read_to_string(&mut contents))
+            .map_err(|e| format!("Could not read rule file: {}", e))?;
+
+        RuleTable::parse(&contents)
+    }
+
+    /// Parse the contents of a `.rule` file. Only the `@RULE`, `@TABLE` and `@COLORS` sections are
+    /// used.
+    pub fn parse(s: &str) -> Result<RuleTable, String> {
+        let mut name = None;
+        let mut section = "";
+        let mut table_lines = Vec::new();
+        let mut color_lines = Vec::new();
+
+        for line in s.lines() {
+            let line = line.split('#').next().unwrap().trim();
+            if let Some(rule) = line.strip_prefix("@RULE") {
+                name = Some(rule.trim().to_string());
+                section = "@RULE";
+            } else if line.starts_with('@') {
+                section = line.split_whitespace().next().unwrap();
+            } else if !line.is_empty() && section == "@TABLE" {
+                table_lines.push(line);
+            } else if !line.is_empty() && section == "@COLORS" {
+                color_lines.push(line);
+            }
+        }
+
+        let name = match name {
+            Some(ref name) if !name.is_empty() => name.clone(),
+            _ => {
+                return Err(String::from(
+                    "The rule file has no `@RULE` line with a name.",
+                ))
+            }
+        };
+        if table_lines.is_empty() {
+            return Err(format!(
+                "The rule file of `{}` has no `@TABLE` section.",
+                name
+            ));
+        }
+
+        let mut table = parse_table(&table_lines)?;
+        table.name = name;
+        table.colors = parse_colors(&color_lines, table.states as usize)?;
+
+        Ok(table)
+    }
+
+    /// The state that a cell changes to, given its state followed by the states of its
+    /// neighbours, in the order of `RuleTable::offsets`.
+    pub fn next_state(&self, inputs: &[u8]) -> u8 {
+        if inputs.iter().any(|&state| state >= self.states) {
+            return inputs[0];
+        }
+
+        let states = self.states as usize;
+        for word in 0..self.words {
+            let matching = inputs
+                .iter()
+                .enumerate()
+                .fold(!0, |matching, (position, &state)| {
+                    matching & self.inputs[(position * states + state as usize) * self.words + word]
+                });
+            if matching != 0 {
+                return self.outputs[word * 64 + matching.trailing_zeros() as usize];
+            }
+        }
+
+        inputs[0]
+    }
+
+    /// The positions of the neighbours of a cell relative to the cell, in the order in which
+    /// they are listed in a transition: clockwise, starting at the top.
+    pub fn offsets(&self) -> &'static [(isize, isize)] {
+        offsets(self.neighbourhood)
+    }
+
+    /// Compute the states of all cells of the board in the next generation, row after row.
+    pub(crate) fn next_states(&self, topology: Topology, board: &Board) -> Vec<u8> {
+        let (width, height) = (board.width(), board.height());
+        let offsets = self.offsets();
+
+        let state = |x: isize, y: isize| -> u8 {
+            match topology.map(x, y, width, height) {
+                Some((x, y)) => board.get(x, y).into(),
+                None => 0,
+            }
+        };
+
+        let mut next = vec![0; width * height];
+        next.par_chunks_mut(width)
+            .enumerate()
+            .for_each(|(y, next_row)| {
+                let y = y as isize;
+                let mut inputs = vec![0; offsets.len() + 1];
+                for (x, next_state) in next_row.iter_mut().enumerate() {
+                    let x = x as isize;
+                    inputs[0] = state(x, y);
+                    for (input, &(dx, dy)) in inputs[1..].iter_mut().zip(offsets) {
+                        *input = state(x + dx, y + dy);
+                    }
+                    *next_state = self.next_state(&inputs);
+                }
+            });
+
+        next
+    }
+}
+
+fn offsets(neighbourhood: Neighbourhood) -> &'static [(isize, isize)] {
+    match neighbourhood {
+        Neighbourhood::VonNeumann => &[(0, -1), (1, 0), (0, 1), (-1, 0)],
+        Neighbourhood::Hexagonal => &[(0, -1), (1, 0), (1, 1), (0, 1), (-1, 0), (-1, -1)],
+        _ => &[
+            (0, -1),
+            (1, -1),
+            (1, 0),
+            (1, 1),
+            (0, 1),
+            (-1, 1),
+            (-1, 0),
+            (-1, -1),
+        ],
+    }
+}
+
+/// Parse the lines of a `@TABLE` section, without comments or empty lines.
+fn parse_table(lines: &[&str]) -> Result<RuleTable, String> {
+    let mut states = None;
+    let mut neighbourhood = Neighbourhood::Moore;
+    let mut symmetries = "none";
+    let mut variables: HashMap<&str, States> = HashMap::new();
+    let mut transitions = Vec::new();
+
+    for &line in lines {
+        if let Some(value) = line.strip_prefix("n_states:") {
+            let n: usize = value
+                .trim()
+                .parse()
+                .map_err(|_| format!("Could not parse the number of states `{}`.", line))?;
+            if !(2..=255).contains(&n) {
+                return Err(format!(
+                    "The number of states should be from 2 to 255: `{}`.",
+                    line
+                ));
+            }
+            states = Some(n);
+        } else if let Some(value) = line.strip_prefix("neighborhood:") {
+            neighbourhood = match value.trim() {
+                "Moore" => Neighbourhood::Moore,
+                "vonNeumann" => Neighbourhood::VonNeumann,
+                "hexagonal" => Neighbourhood::Hexagonal,
+                other => {
+                    return Err(format!(
+                        "Unsupported neighborhood `{}`, expected Moore, vonNeumann or hexagonal.",
+                        other
+                    ))
+                }
+            };
+        } else if let Some(value) = line.strip_prefix("symmetries:") {
+            symmetries = value.trim();
+        } else if let Some(definition) = line.strip_prefix("var ") {
+            let mut parts = definition.splitn(2, '=');
+            let (name, value) = match (parts.next(), parts.next()) {
+                (Some(name), Some(value)) => (name.trim(), value.trim()),
+                _ => return Err(format!("Malformed variable `{}`.", line)),
+            };
+            let value = parse_set(value, &variables)?;
+            variables.insert(name, value);
+        } else {
+            transitions.push(line);
+        }
+    }
+
+    let states = states.ok_or_else(|| String::from("The rule table has no `n_states`."))?;
+    let offsets = offsets(neighbourhood);
+    let group = symmetry_group(symmetries, offsets.len())?;
+
+    let mut expanded = Vec::new();
+    let mut seen = HashSet::new();
+    for line in transitions {
+        for (inputs, output) in expand(line, offsets.len(), &variables)? {
+            let too_large = inputs
+                .iter()
+                .flat_map(|input| (states..256).filter(move |&s| contains(input, s as u8)))
+                .next()
+                .is_some();
+            if too_large || output as usize >= states {
+                return Err(format!(
+                    "Transition `{}` uses states that do not exist.",
+                    line
+                ));
+            }
+
+            for neighbours in apply_symmetries(&group, &inputs[1..]) {
+                let mut inputs = inputs.clone();
+                inputs[1..].copy_from_slice(&neighbours);
+                if seen.insert((inputs.clone(), output)) {
+                    expanded.push((inputs, output));
+                }
+            }
+        }
+    }
+
+    let words = expanded.len().div_ceil(64).max(1);
+    let positions = offsets.len() + 1;
+    let mut inputs = vec![0; positions * states * words];
+    for (t, (transition, _)) in expanded.iter().enumerate() {
+        for (position, input) in transition.iter().enumerate() {
+            for state in (0..states).filter(|&s| contains(input, s as u8)) {
+                inputs[(position * states + state) * words + t / 64] |= 1 << (t % 64);
+            }
+        }
+    }
+
+    Ok(RuleTable {
+        name: String::new(),
+        states: states as u8,
+        neighbourhood,
+        colors: Vec::new(),
+        words,
+        inputs,
+        outputs: expanded.into_iter().map(|(_, output)| output).collect(),
+    })
+}
+
+/// Parse a set of states like `{0,1,2}`, in which variables may be used.
+fn parse_set(s: &str, variables: &HashMap<&str, States>) -> Result<States, String> {
+    let inner = s
+        .strip_prefix('{')
+        .and_then(|s| s.strip_suffix('}'))
+        .ok_or_else(|| format!("Expected a set of states like `{{0,1}}`, found `{}`.", s))?;
+
+    let mut states = [0; 4];
+    for item in inner.split(',').map(str::trim) {
+        let item = parse_state(item).map(single).or_else(|_| {
+            variables
+                .get(item)
+                .cloned()
+                .ok_or_else(|| format!("Unknown state or variable `{}`.", item))
+        })?;
+        for (word, item) in states.iter_mut().zip(&item) {
+            *word |= item;
+        }
+    }
+
+    Ok(states)
+}
+
+fn parse_state(s: &str) -> Result<u8, String> {
+    s.parse()
+        .map_err(|_| format!("Could not parse state `{}`.", s))
+}
+
+/// Split a transition into its inputs and output, which are separated by commas, or written
+/// without separators if all of them are single digits.
+fn split_transition(line: &str) -> Vec<String> {
+    if line.contains(',') {
+        line.split(',').map(|s| s.trim().to_string()).collect()
+    } else if line.contains(char::is_whitespace) {
+        line.split_whitespace().map(String::from).collect()
+    } else {
+        line.chars().map(|c| c.to_string()).collect()
+    }
+}
+
+/// Expand the variables of a transition that are used more than once, which have to take the
+/// same state everywhere they are used. Returns the inputs, as sets of states, and the output of
+/// every combination of their states.
+fn expand(
+    line: &str,
+    neighbours: usize,
+    variables: &HashMap<&str, States>,
+) -> Result<Vec<(Vec<States>, u8)>, String> {
+    let tokens = split_transition(line);
+    if tokens.len() != neighbours + 2 {
+        return Err(format!(
+            "Transition `{}` should have {} states, one for the cell, one for each neighbour and one for the result.",
+            line,
+            neighbours + 2
+        ));
+    }
+
+    let (inputs, output) = tokens.split_at(neighbours + 1);
+    let output = &output[0];
+    let is_bound = |token: &String| {
+        variables.contains_key(token.as_str())
+            && (inputs.iter().filter(|&t| t == token).count() > 1 || token == output)
+    };
+    let mut bound: Vec<&String> = inputs.iter().filter(|t| is_bound(t)).collect();
+    bound.sort();
+    bound.dedup();
+
+    // Every combination of states of the bound variables.
+    let mut bindings: Vec<HashMap<&String, u8>> = vec![HashMap::new()];
+    for &name in &bound {
+        let values = variables[name.as_str()];
+        bindings = bindings
+            .into_iter()
+            .flat_map(|binding| {
+                (0..=255u8)
+                    .filter(move |&s| contains(&values, s))
+                    .map(move |s| {
+                        let mut binding = binding.clone();
+                        binding.insert(name, s);
+                        binding
+                    })
+            })
+            .collect();
+    }
+
+    bindings
+        .into_iter()
+        .map(|binding| {
+            let inputs = inputs
+                .iter()
+                .map(|token| match binding.get(token) {
+                    Some(&state) => Ok(single(state)),
+                    None => parse_state(token).map(single).or_else(|_| {
+                        variables
+                            .get(token.as_str())
+                            .cloned()
+                            .ok_or_else(|| format!("Unknown state or variable `{}`.", token))
+                    }),
+                })
+                .collect::<Result<Vec<States>, String>>()?;
+            let output = match binding.get(output) {
+                Some(&state) => state,
+                None => parse_state(output).map_err(|_| {
+                    format!(
+                        "The result `{}` of transition `{}` should be a state or a variable that is used as input.",
+                        output, line
+                    )
+                })?,
+            };
+
+            Ok((inputs, output))
+        })
+        .collect()
+}
+
+/// The symmetries of a rule table, as the permutations of the neighbours that they allow, or
+/// `None` if every permutation is allowed.
+fn symmetry_group(symmetries: &str, n: usize) -> Result<Option<Vec<Vec<usize>>>, String> {
+    let rotation = |step: usize| -> Vec<usize> { (0..n).map(|i| (i + step) % n).collect() };
+    let reflection: Vec<usize> = (0..n).map(|i| (n - i) % n).collect();
+
+    let (rotations, reflect) = match symmetries {
+        "permute" => return Ok(None),
+        "none" => (1, false),
+        "reflect" => (1, true),
+        _ => {
+            let rest = symmetries.strip_prefix("rotate").unwrap_or("");
+            let (count, reflect) = match rest.strip_suffix("reflect") {
+                Some(count) => (count, true),
+                None => (rest, false),
+            };
+            match count.parse::<usize>() {
+                Ok(count) if count > 0 && n.is_multiple_of(count) => (count, reflect),
+                _ => {
+                    return Err(format!(
+                        "Unsupported symmetries `{}` for a neighbourhood of {} cells.",
+                        symmetries, n
+                    ))
+                }
+            }
+        }
+    };
+
+    let mut group = Vec::new();
+    for r in 0..rotations {
+        let rotated = rotation(r * n / rotations);
+        if reflect {
+            group.push(reflection.iter().map(|&i| rotated[i]).collect());
+        }
+        group.push(rotated);
+    }
+
+    Ok(Some(group))
+}
+
+/// All distinct arrangements of the neighbours that the symmetries allow.
+fn apply_symmetries(group: &Option<Vec<Vec<usize>>>, neighbours: &[States]) -> Vec<Vec<States>> {
+    match *group {
+        Some(ref group) => group
+            .iter()
+            .map(|permutation| permutation.iter().map(|&i| neighbours[i]).collect())
+            .collect(),
+        None => {
+            // Every distinct permutation, in lexicographic order.
+            let mut permutation = neighbours.to_vec();
+            permutation.sort();
+            let mut result = vec![permutation.clone()];
+            while next_permutation(&mut permutation) {
+                result.push(permutation.clone());
+            }
+            result
+        }
+    }
+}
+
+/// Change a sequence into the next larger permutation of its elements. Returns false if it was
+/// the largest permutation.
+fn next_permutation<T: Ord>(items: &mut [T]) -> bool {
+    let i = match (1..items.len()).rev().find(|&i| items[i - 1] < items[i]) {
+        Some(i) => i,
+        None => return false,
+    };
+    let j = (i..items.len())
+        .rev()
+        .find(|&j| items[i - 1] < items[j])
+        .unwrap();
+    items.swap(i - 1, j);
+    items[i..].reverse();
+
+    true
+}
+
+/// Parse the lines of a `@COLORS` section, which are either a state followed by its color, like
+/// `1 255 0 0`, or two colors between which the colors of all states except 0 are interpolated.
+fn parse_colors(lines: &[&str], states: usize) -> Result<Vec<Option<[u8; 3]>>, String> {
+    let mut colors = vec![None; states];
+
+    for line in lines {
+        let numbers = line
+            .split_whitespace()
+            .map(|n| n.parse::<u8>())
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| format!("Could not parse color `{}`.", line))?;
+
+        match numbers[..] {
+            [state, r, g, b] => {
+                if let Some(color) = colors.get_mut(state as usize) {
+                    *color = Some([r, g, b]);
+                }
+            }
+            [r1, g1, b1, r2, g2, b2] => {
+                for (state, color) in colors.iter_mut().enumerate().skip(1) {
+                    let t = (state - 1) as f32 / (states.max(3) - 2) as f32;
+                    let mix =
+                        |a: u8, b: u8| (f32::from(a) + (f32::from(b) - f32::from(a)) * t) as u8;
+                    *color = Some([mix(r1, r2), mix(g1, g2), mix(b1, b2)]);
+                }
+            }
+            _ => return Err(format!("Could not parse color `{}`.", line)),
+        }
+    }
+
+    Ok(colors)
+}
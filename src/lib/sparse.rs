@@ -0,0 +1,290 @@
+use super::automaton::Automaton;
+use super::board;
+use super::parsers::Pattern;
+use super::rayon::prelude::*;
+use super::rule::Rule;
+use super::{Bounds, CellState};
+use std::collections::{HashMap, HashSet};
+
+/// The width and height of a tile, in cells.
+const TILE_SIZE: isize = 64;
+
+/// A square of 64 by 64 cells. Bit `x` of row `y` is set if the cell at `(x, y)` is alive.
+type Tile = [u64; TILE_SIZE as usize];
+
+/// A universe without edges, which only stores the tiles around alive cells.
+/// Tiles are allocated when cells next to them come alive, and freed when all of their cells have
+/// died.
+pub struct SparseLife {
+    rule: Rule,
+    tiles: HashMap<(isize, isize), Tile>,
+    generation: u64,
+}
+
+impl SparseLife {
+    /// Return a new, empty universe that evolves using the given rule.
+    /// Rules in which dead cells with no alive neighbours are born (`B0`) are not supported,
+    /// because they would fill the unbounded universe. Generations rules are not supported either,
+    /// because only alive and dead cells are stored, and neither are Larger than Life rules and
+    /// rule tables.
+    pub fn new(rule: Rule) -> Result<SparseLife, String> {
+        if rule.birth[0] {
+            return Err(format!(
+                "The rule `{}` can not be used in an unbounded universe, because it contains B0.",
+                rule
+            ));
+        }
+        if rule.states > 2 {
+            return Err(format!(
+                "The rule `{}` can not be used in an unbounded universe, because it has dying states.",
+                rule
+            ));
+        }
+        if rule.table.is_some() {
+            return Err(format!(
+                "The rule `{}` can not be used in an unbounded universe, because it is a rule table.",
+                rule
+            ));
+        }
+        if rule.larger_than_life.is_some() {
+            return Err(format!(
+                "The rule `{}` can not be used in an unbounded universe, because it is a Larger than Life rule.",
+                rule
+            ));
+        }
+
+        Ok(SparseLife {
+            rule,
+            tiles: HashMap::new(),
+            generation: 0,
+        })
+    }
+
+    /// Return a new universe with the cells of the pattern.
+    /// If the pattern declares a rule, that rule is used, otherwise Conway's rule is used.
+    pub fn from_pattern(pattern: &Pattern) -> Result<SparseLife, String> {
+        let rule = match pattern.rule {
+            Some(ref rule) => rule.parse()?,
+            None => Rule::default(),
+        };
+
+        let mut sparse_life = SparseLife::new(rule)?;
+        for &(x, y) in &pattern.cells {
+            sparse_life.set(x, y, CellState::Alive);
+        }
+
+        Ok(sparse_life)
+    }
+
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    /// The number of tiles that are currently allocated.
+    pub fn tiles(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Advance the universe by one generation.
+    pub fn update(&mut self) {
+        // Cells can only come alive in the tiles that exist, or next to alive cells on the edges
+        // of those tiles.
+        let mut candidates: HashSet<(isize, isize)> = HashSet::new();
+        for (&(tile_x, tile_y), tile) in &self.tiles {
+            let top = tile[0] != 0;
+            let bottom = tile[TILE_SIZE as usize - 1] != 0;
+            let columns = tile.iter().fold(0, |columns, row| columns | row);
+            let left = columns & 1 != 0;
+            let right = columns >> 63 != 0;
+
+            for dy in -1..2 {
+                for dx in -1..2 {
+                    let needed = (dy != -1 || top)
+                        && (dy != 1 || bottom)
+                        && (dx != -1 || left)
+                        && (dx != 1 || right);
+                    if needed {
+                        candidates.insert((tile_x + dx, tile_y + dy));
+                    }
+                }
+            }
+        }
+
+        let candidates: Vec<(isize, isize)> = candidates.into_iter().collect();
+        let tiles = &self.tiles;
+        let rule = self.rule.clone();
+
+        self.tiles = candidates
+            .par_iter()
+            .map(|&position| (position, next_tile(tiles, &rule, position)))
+            .filter(|(_, tile)| !is_empty(tile))
+            .collect();
+
+        self.generation += 1;
+    }
+}
+
+impl Automaton for SparseLife {
+    fn step(&mut self) {
+        self.update();
+    }
+
+    fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    fn population(&self) -> u64 {
+        self.tiles
+            .values()
+            .flat_map(|tile| tile.iter())
+            .map(|row| u64::from(row.count_ones()))
+            .sum()
+    }
+
+    fn get(&self, x: isize, y: isize) -> CellState {
+        let (tile, (x, y)) = split(x, y);
+        match self.tiles.get(&tile) {
+            Some(tile) => (tile[y] >> x & 1 == 1).into(),
+            None => CellState::Dead,
+        }
+    }
+
+    fn set(&mut self, x: isize, y: isize, state: CellState) {
+        let (position, (x, y)) = split(x, y);
+
+        if state == CellState::Alive {
+            let tile = self
+                .tiles
+                .entry(position)
+                .or_insert([0; TILE_SIZE as usize]);
+            tile[y] |= 1 << x;
+        } else if let Some(tile) = self.tiles.get_mut(&position) {
+            tile[y] &= !(1 << x);
+        }
+
+        if self.tiles.get(&position).is_some_and(is_empty) {
+            self.tiles.remove(&position);
+        }
+    }
+
+    fn bounds(&self) -> Option<Bounds> {
+        self.tiles
+            .iter()
+            .map(|(&(tile_x, tile_y), tile)| {
+                let columns = tile.iter().fold(0, |columns, row| columns | row);
+                let min_y = tile.iter().position(|&row| row != 0).unwrap() as isize;
+                let max_y = tile.iter().rposition(|&row| row != 0).unwrap() as isize;
+                let min_x = columns.trailing_zeros() as isize;
+                let max_x = 63 - columns.leading_zeros() as isize;
+
+                let origin = (tile_x * TILE_SIZE, tile_y * TILE_SIZE);
+                (
+                    (origin.0 + min_x, origin.1 + min_y),
+                    (origin.0 + max_x, origin.1 + max_y),
+                )
+            })
+            .fold(None, |bounds, ((min_x, min_y), (max_x, max_y))| {
+                Some(match bounds {
+                    None => ((min_x, min_y), (max_x, max_y)),
+                    Some(((a, b), (c, d))) => {
+                        ((a.min(min_x), b.min(min_y)), (c.max(max_x), d.max(max_y)))
+                    }
+                })
+            })
+    }
+
+    fn for_each_alive_cell(&self, region: Bounds, f: &mut dyn FnMut(isize, isize)) {
+        let ((min_x, min_y), (max_x, max_y)) = region;
+        let (min_tile, _) = split(min_x, min_y);
+        let (max_tile, _) = split(max_x, max_y);
+
+        for (&(tile_x, tile_y), tile) in &self.tiles {
+            let outside = tile_x < min_tile.0
+                || tile_y < min_tile.1
+                || tile_x > max_tile.0
+                || tile_y > max_tile.1;
+            if outside {
+                continue;
+            }
+
+            let origin = (tile_x * TILE_SIZE, tile_y * TILE_SIZE);
+            for (y, &row) in tile.iter().enumerate() {
+                let y = origin.1 + y as isize;
+                let mut row = row;
+                while row != 0 {
+                    let x = origin.0 + row.trailing_zeros() as isize;
+                    row &= row - 1;
+                    if x >= min_x && x <= max_x && y >= min_y && y <= max_y {
+                        f(x, y);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Split a position into the position of its tile and the position within that tile.
+fn split(x: isize, y: isize) -> ((isize, isize), (usize, usize)) {
+    (
+        (x.div_euclid(TILE_SIZE), y.div_euclid(TILE_SIZE)),
+        (
+            x.rem_euclid(TILE_SIZE) as usize,
+            y.rem_euclid(TILE_SIZE) as usize,
+        ),
+    )
+}
+
+fn is_empty(tile: &Tile) -> bool {
+    tile.iter().all(|&row| row == 0)
+}
+
+/// Compute the next generation of a tile, using the tiles around it.
+fn next_tile(tiles: &HashMap<(isize, isize), Tile>, rule: &Rule, position: (isize, isize)) -> Tile {
+    let empty = [0; TILE_SIZE as usize];
+    let tile_at = |dx: isize, dy: isize| {
+        tiles
+            .get(&(position.0 + dx, position.1 + dy))
+            .unwrap_or(&empty)
+    };
+
+    let (north_west, north, north_east) = (tile_at(-1, -1), tile_at(0, -1), tile_at(1, -1));
+    let (west, middle, east) = (tile_at(-1, 0), tile_at(0, 0), tile_at(1, 0));
+    let (south_west, south, south_east) = (tile_at(-1, 1), tile_at(0, 1), tile_at(1, 1));
+
+    let last = TILE_SIZE as usize - 1;
+    // A row of any tile, with the cells just outside of its left and right edge.
+    let row = |y: isize| -> (u64, u64, u64) {
+        let (west, middle, east) = if y < 0 {
+            (north_west, north, north_east)
+        } else if y > last as isize {
+            (south_west, south, south_east)
+        } else {
+            (west, middle, east)
+        };
+        let y = y.rem_euclid(TILE_SIZE) as usize;
+
+        (west[y] >> 63, middle[y], east[y] & 1)
+    };
+
+    let mut next = [0; TILE_SIZE as usize];
+    for (y, next_row) in next.iter_mut().enumerate() {
+        let y = y as isize;
+        let (above_left, above, above_right) = row(y - 1);
+        let (middle_left, middle, middle_right) = row(y);
+        let (below_left, below, below_right) = row(y + 1);
+
+        let (above_l, above_r) = board::shifted_words(&[above], 0, 64, above_left, above_right);
+        let (middle_l, middle_r) =
+            board::shifted_words(&[middle], 0, 64, middle_left, middle_right);
+        let (below_l, below_r) = board::shifted_words(&[below], 0, 64, below_left, below_right);
+
+        *next_row = board::next_words(
+            rule,
+            (above_l, above, above_r),
+            (middle_l, middle, middle_r),
+            (below_l, below, below_r),
+        );
+    }
+
+    next
+}
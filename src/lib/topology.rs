@@ -0,0 +1,114 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Describes how the edges of the board are joined, which determines the neighbours of the cells
+/// on the edges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Topology {
+    /// A bounded plane: all cells outside of the board are dead.
+    #[default]
+    Plane,
+    /// The left edge is joined to the right edge and the top edge to the bottom edge.
+    Torus,
+    /// Like a torus, but the top and bottom edges are joined with a twist, so crossing them
+    /// mirrors the board horizontally.
+    KleinBottle,
+    /// Both pairs of edges are joined with a twist. The cells diagonally across a corner are dead.
+    CrossSurface,
+    /// The top edge is joined to the left edge and the bottom edge to the right edge. This only
+    /// works on square boards. The cells diagonally across a corner are dead.
+    Sphere,
+}
+
+impl Topology {
+    /// Map a position, which may lie outside of a board of the given size, to the position of the
+    /// cell on the board that it refers to.
+    /// Returns `None` if the position refers to a cell that is always dead. The sphere is only
+    /// defined for square boards, see `GameOfLife::with_topology`.
+    pub fn map(self, x: isize, y: isize, width: usize, height: usize) -> Option<(usize, usize)> {
+        let (w, h) = (width as isize, height as isize);
+        let x_inside = x >= 0 && x < w;
+        let y_inside = y >= 0 && y < h;
+
+        if x_inside && y_inside {
+            return Some((x as usize, y as usize));
+        }
+
+        let (x, y) = match self {
+            Topology::Plane => return None,
+            Topology::Torus => (wrap(x, w), wrap(y, h)),
+            Topology::KleinBottle => {
+                if y_inside {
+                    (wrap(x, w), y)
+                } else {
+                    (wrap(w - 1 - x, w), wrap(y, h))
+                }
+            }
+            Topology::CrossSurface => {
+                if x_inside {
+                    (w - 1 - x, wrap(y, h))
+                } else if y_inside {
+                    (wrap(x, w), h - 1 - y)
+                } else {
+                    return None;
+                }
+            }
+            Topology::Sphere => {
+                if x_inside && y < 0 {
+                    (0, x)
+                } else if x_inside {
+                    (w - 1, x)
+                } else if y_inside && x < 0 {
+                    (y, 0)
+                } else if y_inside {
+                    (y, h - 1)
+                } else {
+                    return None;
+                }
+            }
+        };
+
+        if x >= 0 && x < w && y >= 0 && y < h {
+            Some((x as usize, y as usize))
+        } else {
+            None
+        }
+    }
+}
+
+/// Wrap a coordinate around a length, so that it lies in `0..length`.
+fn wrap(value: isize, length: isize) -> isize {
+    ((value % length) + length) % length
+}
+
+impl FromStr for Topology {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "plane" => Ok(Topology::Plane),
+            "torus" => Ok(Topology::Torus),
+            "klein-bottle" => Ok(Topology::KleinBottle),
+            "cross-surface" => Ok(Topology::CrossSurface),
+            "sphere" => Ok(Topology::Sphere),
+            _ => Err(format!(
+                "Unknown topology `{}`, expected `plane`, `torus`, `klein-bottle`, `cross-surface` or `sphere`.",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Topology {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            Topology::Plane => "plane",
+            Topology::Torus => "torus",
+            Topology::KleinBottle => "klein-bottle",
+            Topology::CrossSurface => "cross-surface",
+            Topology::Sphere => "sphere",
+        };
+
+        write!(f, "{}", name)
+    }
+}
@@ -3,17 +3,36 @@ extern crate clap;
 extern crate game_of_life;
 extern crate gl;
 extern crate glutin;
+extern crate rand;
+extern crate termion;
 
 mod config;
+mod edit;
 mod graphics_context;
+mod playback;
 mod render;
+mod run;
+mod selection;
+mod simulation;
+mod terminal;
 mod view;
 
 use config::Config;
+use edit::Editor;
+use game_of_life::automaton::Automaton;
+use game_of_life::parsers::Pattern;
 use game_of_life::GameOfLife;
+use game_of_life::{animation, image};
 use glutin::dpi::*;
 use glutin::GlContext;
+use playback::Playback;
 use render::Renderer;
+use selection::Selector;
+use simulation::Simulation;
+use std::fs::File;
+use std::io::BufWriter;
+use std::process;
+use std::thread;
 use std::time::{Duration, Instant};
 use view::View;
 
@@ -21,25 +40,57 @@ fn main() {
     let mut config = Config::parse();
 
     let mut game_of_life = GameOfLife::new(config.width as usize, config.height as usize);
+    if let Err(e) = game_of_life.with_topology(config.topology) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+    if let Err(e) = reinitialise(&mut game_of_life, &config) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
 
-    if let Some(f) = config.file.clone() {
-        game_of_life.init_with_file(f).unwrap();
-    } else {
-        game_of_life.init_randomly(config.chance);
+    // Compute generations without opening a window.
+    if let Some(ref options) = config.run {
+        if let Err(e) = run::run(&mut game_of_life, options) {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+        return;
     }
 
-    let name = if let Some(ref n) = game_of_life.name {
-        format!("Game of Life - {}", n)
-    } else {
-        String::from("Game of Life")
-    };
+    // Write an image or GIF of the board instead of opening a window.
+    if config.image.is_some() || config.gif.is_some() {
+        if let Err(e) = export(&mut game_of_life, &config) {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+        return;
+    }
+
+    // Only the window and the terminal can step back, so the history is not kept without them.
+    game_of_life.with_history(config.history);
+
+    if config.tui {
+        if let Err(e) = terminal::run(config, game_of_life) {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+        return;
+    }
+
+    let mut title = window_title(&game_of_life, &Playback::from_config(&config).describe());
+    let simulation = Simulation::new(
+        game_of_life,
+        Playback::from_config(&config),
+        config.frame_delay(),
+    );
 
     // Create View for managing boards larger than a window
     let mut view = View::from_config(&config);
 
     // Create window.
     let mut events_loop = glutin::EventsLoop::new();
-    let window = glutin::WindowBuilder::new().with_title(name);
+    let window = glutin::WindowBuilder::new().with_title(title.clone());
 
     let monitor: glutin::MonitorId = events_loop.get_primary_monitor(); // Get the MonitorId of the primary monitor.
     let dimensions = monitor.get_dimensions(); // Get the screen dimensions.
@@ -64,7 +115,7 @@ fn main() {
         gl_window.make_current().unwrap();
     }
 
-    let renderer = Renderer::new(&gl_window).unwrap();
+    let mut renderer = Renderer::new(&gl_window).unwrap();
 
     // Capture the cursor if appropriate.
     if view.capture_cursor {
@@ -72,13 +123,16 @@ fn main() {
         gl_window.window().hide_cursor(true);
     }
 
-    let delay = if config.fps == 0 {
-        Duration::from_millis(0)
-    } else {
-        Duration::from_millis(((1.0 / config.fps as f32) * 1e3) as u64)
-    };
-    // Keep track of the previous time the board had been updated, to use the fps config variable.
-    let mut previous_update = Instant::now() - delay;
+    let delay = config.frame_delay();
+    // Keep track of the previous time a frame had been rendered, to use the fps config variable.
+    let mut previous_frame = Instant::now() - delay;
+
+    let mut editor = Editor::default();
+    let mut selector = Selector::default();
+    // The position of the cursor in the window.
+    let mut cursor = (0.0, 0.0);
+    // Whether the board is being dragged with the mouse.
+    let mut dragging = false;
 
     let mut closed = false;
     while !closed {
@@ -90,25 +144,71 @@ fn main() {
                 }
                 glutin::WindowEvent::Resized(size) => {
                     // Window was resized.
-                    // let center = view.get_center();
+                    let center = view.get_center();
                     view.on_resize(size.width as f32, size.height as f32);
-                    // view.set_center(center);
+                    view.set_center(center);
 
                     let dpi_factor = gl_window.get_hidpi_factor();
                     gl_window.resize(size.to_physical(dpi_factor));
                 }
-                glutin::WindowEvent::MouseInput { state, button, .. } => {
-                    // Left-mouse-button pressed.
-                    if state == glutin::ElementState::Pressed && glutin::MouseButton::Left == button
-                    {
-                        // Reinitialise board.
-                        if let Some(f) = config.file.clone() {
-                            game_of_life.init_with_file(f).unwrap();
-                        } else {
-                            game_of_life.init_randomly(config.chance);
+                glutin::WindowEvent::CursorMoved { position, .. } => {
+                    if dragging {
+                        view.drag(position.x - cursor.0, position.y - cursor.1);
+                    }
+                    cursor = (position.x, position.y);
+                    let cell = view.cell_at(cursor.0, cursor.1, config.hex_cells);
+                    if editor.is_drawing() {
+                        simulation.update(|state| editor.extend(&mut state.game_of_life, cell));
+                    }
+                    if selector.is_selecting() {
+                        selector.extend(cell);
+                    }
+                }
+                glutin::WindowEvent::MouseInput {
+                    state: glutin::ElementState::Pressed,
+                    button,
+                    ..
+                } => {
+                    let cell = view.cell_at(cursor.0, cursor.1, config.hex_cells);
+                    match button {
+                        // Paste the clipboard at the cursor, or cancel pasting.
+                        glutin::MouseButton::Left if selector.pasting => {
+                            simulation.update(|state| selector.paste(&mut state.game_of_life, cell))
                         }
+                        glutin::MouseButton::Right if selector.pasting => {
+                            selector.pasting = false;
+                        }
+                        // Select a rectangle, starting at the cursor.
+                        glutin::MouseButton::Left if selector.enabled => selector.begin(cell),
+                        // Draw or erase cells, starting at the cursor.
+                        glutin::MouseButton::Left if editor.enabled => simulation
+                            .update(|state| editor.begin(&mut state.game_of_life, cell, false)),
+                        glutin::MouseButton::Right if editor.enabled => simulation
+                            .update(|state| editor.begin(&mut state.game_of_life, cell, true)),
+                        // Drag the board to move the view.
+                        glutin::MouseButton::Middle | glutin::MouseButton::Right => {
+                            dragging = true;
+                        }
+                        glutin::MouseButton::Left => {
+                            // Left-mouse-button pressed.
+                            // Reinitialise board.
+                            if let Err(e) = simulation
+                                .update(|state| reinitialise(&mut state.game_of_life, &config))
+                            {
+                                eprintln!("{}", e);
+                            }
+                        }
+                        _ => (),
                     }
                 }
+                glutin::WindowEvent::MouseInput {
+                    state: glutin::ElementState::Released,
+                    ..
+                } => {
+                    editor.end();
+                    selector.end();
+                    dragging = false;
+                }
                 glutin::WindowEvent::MouseWheel {
                     delta, modifiers, ..
                 } => {
@@ -117,83 +217,282 @@ fn main() {
                     if modifiers.ctrl {
                         match delta {
                             MouseScrollDelta::LineDelta(_x, y) => {
-                                view.on_scroll(y);
+                                view.on_scroll(y, cursor.0, cursor.1);
                             }
                             MouseScrollDelta::PixelDelta(LogicalPosition { y, .. }) => {
-                                view.on_scroll(y as f32);
+                                view.on_scroll(y as f32, cursor.0, cursor.1);
                             }
                         }
                     }
                 }
-                glutin::WindowEvent::KeyboardInput { input, .. } => {
-                    if input.state == glutin::ElementState::Pressed
-                        && input.virtual_keycode.is_some()
-                    {
-                        use glutin::VirtualKeyCode::*;
-
-                        match input.virtual_keycode.unwrap() {
-                            C => {
-                                // Toggle capture_cursor.
-                                view.toggle_capture_cursor();
-
-                                if view.capture_cursor {
-                                    gl_window.window().grab_cursor(true).unwrap();
-                                    gl_window.window().hide_cursor(true);
-                                } else {
-                                    gl_window.window().grab_cursor(false).unwrap();
-                                    gl_window.window().hide_cursor(false);
-                                }
+                glutin::WindowEvent::KeyboardInput {
+                    input:
+                        glutin::KeyboardInput {
+                            state: glutin::ElementState::Pressed,
+                            virtual_keycode: Some(keycode),
+                            modifiers,
+                            ..
+                        },
+                    ..
+                } => {
+                    use glutin::VirtualKeyCode::*;
+
+                    match keycode {
+                        // Copy, cut or paste the selection with ctrl.
+                        C if modifiers.ctrl => {
+                            let frame = simulation.frame();
+                            selector.copy(&*frame);
+                        }
+                        X if modifiers.ctrl => {
+                            simulation.update(|state| selector.cut(&mut state.game_of_life));
+                        }
+                        V if modifiers.ctrl => selector.start_pasting(),
+                        C => {
+                            // Toggle capture_cursor.
+                            view.toggle_capture_cursor();
+
+                            if view.capture_cursor {
+                                gl_window.window().grab_cursor(true).unwrap();
+                                gl_window.window().hide_cursor(true);
+                            } else {
+                                gl_window.window().grab_cursor(false).unwrap();
+                                gl_window.window().hide_cursor(false);
                             }
-                            F => {
-                                // Use ctrl-cmd F for fullscreen.
-                                if input.modifiers.ctrl && input.modifiers.logo {
-                                    let monitor_id = gl_window.get_current_monitor();
-                                    let size = monitor_id.get_dimensions();
-
-                                    gl_window.window().set_fullscreen(Some(monitor_id));
-                                    view.on_resize(size.width as f32, size.height as f32);
-                                }
+                        }
+                        E => {
+                            // Toggle editing, which needs the cursor.
+                            editor.toggle();
+                            if editor.enabled {
+                                selector.enabled = false;
+                                release_cursor(&mut view, &gl_window);
                             }
-                            Escape => {
-                                // Window has to close.
-                                closed = true;
+                        }
+                        M => {
+                            // Toggle selecting, which needs the cursor.
+                            selector.toggle();
+                            if selector.enabled {
+                                editor.enabled = false;
+                                release_cursor(&mut view, &gl_window);
                             }
-                            Space => {
-                                // Reinitialise board.
-                                if let Some(f) = config.file.clone() {
-                                    game_of_life.init_with_file(f).unwrap();
-                                } else {
-                                    game_of_life.init_randomly(config.chance);
-                                }
+                        }
+                        // Use ctrl-cmd F for fullscreen.
+                        F if modifiers.ctrl && modifiers.logo => {
+                            let monitor_id = gl_window.get_current_monitor();
+                            let size = monitor_id.get_dimensions();
+
+                            gl_window.window().set_fullscreen(Some(monitor_id));
+                            view.on_resize(size.width as f32, size.height as f32);
+                        }
+                        Delete | Back => {
+                            simulation.update(|state| selector.clear(&mut state.game_of_life));
+                        }
+                        F => simulation.update(|state| selector.fill(&mut state.game_of_life)),
+                        N => simulation.update(|state| {
+                            selector.randomise(&mut state.game_of_life, config.chance)
+                        }),
+                        R => simulation
+                            .update(|state| selector.rotate_clockwise(&mut state.game_of_life)),
+                        H => simulation
+                            .update(|state| selector.flip_horizontally(&mut state.game_of_life)),
+                        V => simulation
+                            .update(|state| selector.flip_vertically(&mut state.game_of_life)),
+                        Left | A => view.pan(-pan_distance(view.cells_on_width), 0.0),
+                        Right | D => view.pan(pan_distance(view.cells_on_width), 0.0),
+                        Up | W => view.pan(0.0, -pan_distance(view.cells_on_height)),
+                        Down | S => view.pan(0.0, pan_distance(view.cells_on_height)),
+                        Z => {
+                            // Zoom to fit the alive cells.
+                            if let Some(bounds) = simulation.frame().bounds() {
+                                view.zoom_to_fit(bounds);
+                            }
+                        }
+                        Key0 | Numpad0 => view.reset(),
+                        Escape => {
+                            // Window has to close.
+                            closed = true;
+                        }
+                        Space => {
+                            // Reinitialise board.
+                            if let Err(e) = simulation
+                                .update(|state| reinitialise(&mut state.game_of_life, &config))
+                            {
+                                eprintln!("{}", e);
                             }
-                            _ => (),
                         }
+                        P => {
+                            // Pause or resume.
+                            simulation.update_playback(|playback| playback.toggle_pause());
+                        }
+                        Period => {
+                            // Pause and advance one generation.
+                            simulation.update_playback(|playback| playback.step());
+                        }
+                        Equals | Add => {
+                            simulation.update_playback(|playback| playback.faster());
+                        }
+                        Minus | Subtract => {
+                            simulation.update_playback(|playback| playback.slower());
+                        }
+                        Comma => {
+                            // Step back one generation.
+                            simulation.update(|state| state.game_of_life.step_back());
+                        }
+                        Home => {
+                            // Go back to the oldest generation that is kept.
+                            simulation.update(|state| {
+                                if let Some(oldest) = state.game_of_life.history.oldest() {
+                                    state.game_of_life.rewind(oldest).unwrap();
+                                }
+                            });
+                        }
+                        _ => (),
                     }
                 }
                 glutin::WindowEvent::DroppedFile(f) => {
                     if let Some(f) = f.to_str() {
-                        game_of_life.init_with_file(f).unwrap();
-                        config.file = Some(f.to_string());
+                        if selector.enabled {
+                            // Paste the file instead of reinitialising the board with it.
+                            match Pattern::from_file(f) {
+                                Ok(pattern) => {
+                                    selector.clipboard = Some(pattern);
+                                    selector.start_pasting();
+                                }
+                                Err(e) => eprintln!("{}", e),
+                            }
+                        } else {
+                            match simulation
+                                .update(|state| load_file(&mut state.game_of_life, f, &config))
+                            {
+                                Ok(()) => config.file = Some(f.to_string()),
+                                Err(e) => eprintln!("{}", e),
+                            }
+                        }
                     }
                 }
                 _ => (),
             },
-            glutin::Event::DeviceEvent { event, .. } => {
-                if let glutin::DeviceEvent::MouseMotion { delta } = event {
-                    // Mouse moved.
-                    view.on_mouse_move(delta.0, -delta.1);
-                }
+            glutin::Event::DeviceEvent {
+                event: glutin::DeviceEvent::MouseMotion { delta },
+                ..
+            } => {
+                // Mouse moved.
+                view.on_mouse_move(delta.0, -delta.1);
             }
             _ => (),
         });
 
-        renderer.render(&config, &view, &game_of_life);
+        let now = Instant::now();
+        if now.duration_since(previous_frame) < delay {
+            thread::sleep(delay - now.duration_since(previous_frame));
+            continue;
+        }
+        previous_frame = now;
+
+        let mut playback = simulation.describe_playback();
+        if editor.enabled {
+            playback.push_str(", editing");
+        }
+        if selector.enabled {
+            playback.push_str(", selecting");
+        }
+        let new_title = {
+            let frame = simulation.frame();
+            renderer.render(&config, &view, &*frame);
+            window_title(&frame, &playback)
+        };
+        if let Some(selection) = selector.selection {
+            renderer.render_selection(&config, &view, selection);
+        }
+        if let Some(ghost) = selector.ghost() {
+            let cell = view.cell_at(cursor.0, cursor.1, config.hex_cells);
+            renderer.render_ghost(&config, &view, ghost, cell);
+        }
         gl_window.swap_buffers().unwrap();
 
-        let now = Instant::now();
-        if now.duration_since(previous_update) >= delay {
-            game_of_life.update();
-            previous_update = now;
+        if new_title != title {
+            gl_window.window().set_title(&new_title);
+            title = new_title;
         }
     }
 }
+
+/// The number of cells that the arrow keys move the view, which is a tenth of the cells in view.
+fn pan_distance(cells_in_view: usize) -> f64 {
+    (cells_in_view as f64 / 10.0).max(1.0)
+}
+
+/// Stop capturing the cursor, so that it can be used to point at cells.
+fn release_cursor(view: &mut View, gl_window: &glutin::GlWindow) {
+    if view.capture_cursor {
+        view.toggle_capture_cursor();
+        gl_window.window().grab_cursor(false).unwrap();
+        gl_window.window().hide_cursor(false);
+    }
+}
+
+/// The title of the window, with the name of the pattern, the generation and the playback.
+fn window_title(game_of_life: &GameOfLife, playback: &str) -> String {
+    let name = if let Some(ref n) = game_of_life.name {
+        format!("Game of Life - {}", n)
+    } else {
+        String::from("Game of Life")
+    };
+
+    format!(
+        "{} - generation {} ({})",
+        name, game_of_life.generation, playback
+    )
+}
+
+/// Initialise the board with the file in the config, or randomly if no file was given.
+fn reinitialise(game_of_life: &mut GameOfLife, config: &Config) -> Result<(), String> {
+    match config.file {
+        Some(ref f) => load_file(game_of_life, f, config),
+        None => {
+            game_of_life.init_randomly(config.chance);
+            if let Some(ref rule) = config.rule {
+                game_of_life.with_rule(rule.clone());
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Initialise the board with a file.
+/// A rule passed in the config takes precedence over the rule declared by the file, which is then
+/// not read at all, so a rule that can not be parsed does not stop the file from loading.
+fn load_file(game_of_life: &mut GameOfLife, path: &str, config: &Config) -> Result<(), String> {
+    let mut pattern = Pattern::from_file(path)?;
+    if config.rule.is_some() {
+        pattern.rule = None;
+    }
+    game_of_life.init_with_pattern(&pattern)?;
+
+    if let Some(ref rule) = config.rule {
+        game_of_life.with_rule(rule.clone());
+    }
+    Ok(())
+}
+
+/// Write the image and the GIF of the viewport of the board that are asked for by the config.
+fn export(game_of_life: &mut GameOfLife, config: &Config) -> Result<(), String> {
+    let viewport = config.viewport();
+    let style = config.style();
+
+    if let Some(ref path) = config.image {
+        image::render(game_of_life, &viewport, &style).save(path)?;
+    }
+    if let Some(ref path) = config.gif {
+        let file = File::create(path).map_err(|e| e.to_string())?;
+        animation::record(
+            game_of_life,
+            &viewport,
+            &style,
+            config.frames,
+            Duration::from_millis(config.delay),
+            BufWriter::new(file),
+        )?;
+    }
+
+    Ok(())
+}
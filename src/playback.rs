@@ -0,0 +1,103 @@
+use super::Config;
+use std::time::{Duration, Instant};
+
+/// The highest number of updates per second that `faster` sets. Going faster than this computes
+/// more generations per update instead.
+const MAX_GPS: u64 = 1000;
+
+/// The highest number of generations that are computed per update.
+const MAX_GENERATIONS_PER_UPDATE: u64 = 1 << 16;
+
+/// Whether and how fast the board is updated.
+pub struct Playback {
+    pub paused: bool,
+    /// The number of updates per second, or 0 for no limit.
+    pub gps: u64,
+    /// The number of generations that are computed per update.
+    pub generations_per_update: u64,
+    /// The number of single steps that have been asked for while paused.
+    steps: u64,
+    previous_update: Option<Instant>,
+}
+
+impl Playback {
+    pub fn from_config(config: &Config) -> Self {
+        Playback {
+            paused: false,
+            gps: config.gps,
+            generations_per_update: 1,
+            steps: 0,
+            previous_update: None,
+        }
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    /// Pause and advance one generation.
+    pub fn step(&mut self) {
+        self.paused = true;
+        self.steps += 1;
+    }
+
+    /// Double the number of updates per second, or the number of generations per update once
+    /// the updates can not get more frequent.
+    pub fn faster(&mut self) {
+        if self.generations_per_update > 1 || self.gps == 0 || self.gps >= MAX_GPS {
+            self.generations_per_update =
+                (self.generations_per_update * 2).min(MAX_GENERATIONS_PER_UPDATE);
+        } else {
+            self.gps = (self.gps * 2).min(MAX_GPS);
+        }
+    }
+
+    /// Undo `faster`: halve the number of generations per update, or the number of updates per
+    /// second once only one generation is computed per update.
+    pub fn slower(&mut self) {
+        if self.generations_per_update > 1 {
+            self.generations_per_update /= 2;
+        } else {
+            let gps = if self.gps == 0 { MAX_GPS } else { self.gps };
+            self.gps = (gps / 2).max(1);
+        }
+    }
+
+    /// The number of generations that should be computed now, which is 0 if the board is paused
+    /// or was updated too recently.
+    pub fn due(&mut self, now: Instant) -> u64 {
+        if self.paused {
+            let steps = self.steps;
+            self.steps = 0;
+            return steps;
+        }
+
+        let delay = if self.gps == 0 {
+            Duration::from_millis(0)
+        } else {
+            Duration::from_millis(((1.0 / self.gps as f32) * 1e3) as u64)
+        };
+        match self.previous_update {
+            Some(previous) if now.duration_since(previous) < delay => 0,
+            _ => {
+                self.previous_update = Some(now);
+                self.generations_per_update
+            }
+        }
+    }
+
+    /// A description of the playback, like `paused` or `24 gps, 8 generations per update`.
+    pub fn describe(&self) -> String {
+        let speed = if self.gps == 0 {
+            String::from("unlimited gps")
+        } else {
+            format!("{} gps", self.gps)
+        };
+
+        match (self.paused, self.generations_per_update) {
+            (true, _) => String::from("paused"),
+            (false, 1) => speed,
+            (false, n) => format!("{}, {} generations per update", speed, n),
+        }
+    }
+}
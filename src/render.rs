@@ -1,7 +1,9 @@
-use super::cgmath::{Matrix4, Vector3};
-use super::game_of_life::{CellState, GameOfLife};
+use super::game_of_life::automaton::Automaton;
+use super::game_of_life::image::{alive_blocks, mix, state_color};
+use super::game_of_life::parsers::Pattern;
+use super::game_of_life::Bounds;
 use super::glutin;
-use super::graphics_context::GraphicsContext;
+use super::graphics_context::{GraphicsContext, Instance};
 use super::view::View;
 use super::Config;
 
@@ -31,45 +33,167 @@ impl Square {
         }
     }
 
-    /// Takes a position on the board and calculates the coordinates.
+    /// Takes a position in the view and calculates the coordinates, shifted by the part of the
+    /// top-left cell that lies outside of the window.
     pub fn simple(view: &super::View, board_x: usize, board_y: usize) -> Self {
-        let gl_y = board_y as f32 / (view.window_height / view.cell_width) * 2.0 - 1.0;
-        let gl_x = board_x as f32 / (view.window_width / view.cell_width) * 2.0 - 1.0;
+        let (offset_x, offset_y) = view.offset();
+        let gl_y = (board_y as f32 - offset_y) / (view.window_height / view.cell_width) * 2.0 - 1.0;
+        let gl_x = (board_x as f32 - offset_x) / (view.window_width / view.cell_width) * 2.0 - 1.0;
 
         Square::new(view.gl_cell_width(), view.gl_cell_height(), gl_x, gl_y)
     }
+
+    /// Takes a position on the board and calculates the coordinates of the rectangle of `width` by
+    /// `height` cells that starts there. The position may lie outside of the view.
+    pub fn block(view: &super::View, x: isize, y: isize, width: usize, height: usize) -> Self {
+        let (offset_x, offset_y) = view.offset();
+        let (x, y) = ((x - view.x as isize) as f32, (y - view.y as isize) as f32);
+        let gl_y = (y - offset_y) / (view.window_height / view.cell_width) * 2.0 - 1.0;
+        let gl_x = (x - offset_x) / (view.window_width / view.cell_width) * 2.0 - 1.0;
+
+        Square::new(
+            view.gl_cell_width() * width as f32,
+            view.gl_cell_height() * height as f32,
+            gl_x,
+            gl_y,
+        )
+    }
+
+    /// Takes a position on the board and calculates the coordinates on a skewed grid, on which
+    /// the hexagonal neighbourhood looks like a regular hexagon.
+    /// Every row is shifted half a cell to the left of the row above it, around the middle row of
+    /// the view.
+    pub fn skewed(view: &super::View, board_x: usize, board_y: usize) -> Self {
+        let mut square = Square::simple(view, board_x, board_y);
+        let rows_below_middle = board_y as f32 - view.cells_on_height as f32 / 2.0;
+        square.origin.x -= rows_below_middle * view.gl_cell_width() / 2.0;
+
+        square
+    }
 }
 
+/// The color of the outline of the selection.
+const SELECTION_COLOR: [f32; 4] = [0.2, 0.5, 1.0, 1.0];
+
 pub struct Renderer {
     pub graphics_context: GraphicsContext,
+    /// The squares that are drawn at the next flush.
+    instances: Vec<Instance>,
 }
 
 impl Renderer {
     pub fn new(gl_window: &glutin::GlWindow) -> Result<Self, String> {
         let mut graphics_context = GraphicsContext::new();
         graphics_context.init(gl_window)?;
-        Ok(Renderer { graphics_context })
+        Ok(Renderer {
+            graphics_context,
+            instances: Vec::new(),
+        })
     }
 
-    pub fn render(&self, config: &Config, view: &View, game_of_life: &GameOfLife) {
+    pub fn render(&mut self, config: &Config, view: &View, automaton: &dyn Automaton) {
         self.clear_screen(config.background);
 
         assert!(view.cells_on_width - 1 + view.x < view.board_width);
         assert!(view.cells_on_height - 1 + view.y < view.board_height);
 
-        for board_y in 0..view.cells_on_height {
-            for board_x in 0..view.cells_on_width {
-                if game_of_life.board[board_y + view.y][board_x + view.x] == CellState::Alive
-                    || (config.view_border
-                        && (board_y + view.y == 0
-                            || board_y + view.y + 1 == view.board_height
-                            || board_x + view.x == 0
-                            || board_x + view.x + 1 == view.board_width))
-                {
-                    let square = Square::simple(view, board_x, board_y);
-                    self.draw_square(&square, config.foreground);
-                }
+        let viewport = view.viewport();
+
+        // Draw blocks of cells that share a pixel, instead of every cell, when zoomed out.
+        let block = viewport.block();
+        if block > 1 {
+            for block in alive_blocks(automaton, viewport.region, block) {
+                let square = Square::block(view, block.x, block.y, block.width, block.height);
+                let t = block.shade(config.shading);
+                self.draw_square(&square, mix(config.background, config.foreground, t));
+            }
+        } else {
+            let style = config.style();
+            let states = automaton.states();
+            automaton.for_each_cell(viewport.region, &mut |x, y, state| {
+                let square = square(config, view, x as usize - view.x, y as usize - view.y);
+                self.draw_square(&square, state_color(&style, automaton, states, state));
+            });
+        }
+
+        if config.view_border {
+            self.render_border(config, view, block);
+        }
+
+        self.flush();
+    }
+
+    /// Draw the cells, or the blocks of cells when zoomed out, at the edges of the board.
+    /// The blocks are aligned like the blocks of `alive_blocks`.
+    fn render_border(&mut self, config: &Config, view: &View, block: usize) {
+        let (last_x, last_y) = (view.board_width - 1, view.board_height - 1);
+        let (left, top) = (view.x / block * block, view.y / block * block);
+        let right = (view.x + view.cells_on_width - 1).min(last_x);
+        let bottom = (view.y + view.cells_on_height - 1).min(last_y);
+        let columns = (left..right + 1).step_by(block);
+        let rows = (top..bottom + 1).step_by(block);
+
+        let edges = columns
+            .clone()
+            .flat_map(|x| vec![(x, top), (x, bottom / block * block)])
+            .chain(rows.flat_map(|y| vec![(left, y), (right / block * block, y)]));
+        for (x, y) in edges {
+            if x != 0 && y != 0 && x + block <= last_x && y + block <= last_y {
+                continue;
             }
+
+            let square = if block > 1 {
+                let (width, height) = (block.min(last_x + 1 - x), block.min(last_y + 1 - y));
+                Square::block(view, x as isize, y as isize, width, height)
+            } else {
+                square(config, view, x - view.x, y - view.y)
+            };
+            self.draw_square(&square, config.foreground);
+        }
+    }
+
+    /// Draw the outline of the selection.
+    pub fn render_selection(&mut self, config: &Config, view: &View, selection: Bounds) {
+        let ((min_x, min_y), (max_x, max_y)) = selection;
+        for x in min_x..max_x + 1 {
+            self.draw_cell(config, view, x, min_y, SELECTION_COLOR);
+            self.draw_cell(config, view, x, max_y, SELECTION_COLOR);
+        }
+        for y in min_y..max_y + 1 {
+            self.draw_cell(config, view, min_x, y, SELECTION_COLOR);
+            self.draw_cell(config, view, max_x, y, SELECTION_COLOR);
+        }
+
+        self.flush();
+    }
+
+    /// Draw the cells of a pattern that is about to be pasted, with its top-left corner at the
+    /// given cell, halfway between the foreground and background colors.
+    pub fn render_ghost(
+        &mut self,
+        config: &Config,
+        view: &View,
+        pattern: &Pattern,
+        origin: (isize, isize),
+    ) {
+        let color = mix(config.foreground, config.background, 0.5);
+
+        for &(x, y) in &pattern.cells {
+            self.draw_cell(config, view, origin.0 + x, origin.1 + y, color);
+        }
+
+        self.flush();
+    }
+
+    /// Draw the cell at a position on the board, if it is in view.
+    fn draw_cell(&mut self, config: &Config, view: &View, x: isize, y: isize, color: [f32; 4]) {
+        let (x, y) = (x - view.x as isize, y - view.y as isize);
+        if x >= 0
+            && y >= 0
+            && (x as usize) < view.cells_on_width
+            && (y as usize) < view.cells_on_height
+        {
+            self.draw_square(&square(config, view, x as usize, y as usize), color);
         }
     }
 
@@ -77,16 +201,32 @@ impl Renderer {
         GraphicsContext::clear_color(color[0], color[1], color[2], color[3]);
     }
 
-    fn draw_square(&self, square: &Square, color: [f32; 4]) {
-        let scale =
-            Matrix4::from_nonuniform_scale(square.size.width / 2.0, square.size.height / 2.0, 1.0);
-        let translate = Matrix4::from_translation(Vector3::<f32>::new(
-            square.origin.x + square.size.width / 2.0,
-            // Negative, because OpenGL's y-axis is opposite to the one used by game of life.
-            -(square.origin.y + square.size.height / 2.0),
-            0.0,
-        ));
-        self.graphics_context
-            .draw_square_with_scale_translation_color(scale, translate, color);
+    /// Queue a square to be drawn at the next flush.
+    fn draw_square(&mut self, square: &Square, color: [f32; 4]) {
+        self.instances.push(Instance {
+            square: [
+                square.origin.x,
+                square.origin.y,
+                square.size.width,
+                square.size.height,
+            ],
+            color,
+        });
+    }
+
+    /// Draw all queued squares at once.
+    fn flush(&mut self) {
+        self.graphics_context.draw_squares(&self.instances);
+        self.instances.clear();
+    }
+}
+
+/// The square of a cell at a position in the view, skewed if the cells are drawn on a hexagonal
+/// grid.
+fn square(config: &Config, view: &View, x: usize, y: usize) -> Square {
+    if config.hex_cells {
+        Square::skewed(view, x, y)
+    } else {
+        Square::simple(view, x, y)
     }
 }
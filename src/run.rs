@@ -0,0 +1,116 @@
+use game_of_life::automaton::Automaton;
+use game_of_life::parsers::{rle, Pattern};
+use game_of_life::period::Periods;
+use game_of_life::GameOfLife;
+use std::str::FromStr;
+use std::time::Instant;
+
+/// When a run stops before all of its generations have been computed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopCondition {
+    /// Stop once no cells are alive.
+    Extinct,
+    /// Stop once the board is the same as in an earlier generation, because it has become a still
+    /// life or an oscillator.
+    Periodic,
+}
+
+impl FromStr for StopCondition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "extinct" => Ok(StopCondition::Extinct),
+            "periodic" => Ok(StopCondition::Periodic),
+            _ => Err(format!(
+                "Unknown stop condition `{}`, expected `extinct` or `periodic`.",
+                s
+            )),
+        }
+    }
+}
+
+/// The options of the `run` subcommand.
+pub struct Run {
+    pub generations: u64,
+    pub until: Option<StopCondition>,
+    /// The file that the final pattern is written to, or `None` to print it as RLE.
+    pub output: Option<String>,
+}
+
+/// Compute the generations of a run without opening a window, then write the final pattern and
+/// print the population and how long the run took.
+/// The pattern is printed on the standard output if no output file is given, so the statistics are
+/// printed on the standard error then.
+pub fn run(game_of_life: &mut GameOfLife, run: &Run) -> Result<(), String> {
+    // Nothing is stepped back to, so the history would only slow the run down.
+    game_of_life.with_history(0);
+
+    let start = Instant::now();
+    let first_generation = game_of_life.generation;
+    let mut periods = Periods::new();
+    let mut stopped = None;
+
+    while game_of_life.generation - first_generation < run.generations {
+        match run.until {
+            Some(StopCondition::Extinct) if game_of_life.population() == 0 => {
+                stopped = Some(String::from("no cells are alive"));
+            }
+            Some(StopCondition::Periodic) => {
+                if let Some(previous) = periods.check(game_of_life) {
+                    stopped = Some(format!(
+                        "the board is the same as in generation {} (period {})",
+                        previous,
+                        game_of_life.generation - previous
+                    ));
+                }
+            }
+            _ => (),
+        }
+        if stopped.is_some() {
+            break;
+        }
+
+        game_of_life.update();
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+    let generations = game_of_life.generation - first_generation;
+
+    let mut pattern = match game_of_life.bounds() {
+        Some(bounds) => Pattern::from_automaton(game_of_life, bounds),
+        None => Pattern::default(),
+    };
+    pattern.name = game_of_life.name.clone();
+    pattern.rule = Some(game_of_life.rule.to_string());
+
+    let mut report = format!(
+        "Generation {}, population {}, computed {} generations in {:.3} s",
+        game_of_life.generation,
+        game_of_life.population(),
+        generations,
+        elapsed
+    );
+    if elapsed > 0.0 {
+        report.push_str(&format!(
+            " ({:.0} generations per second)",
+            generations as f64 / elapsed
+        ));
+    }
+    report.push('.');
+    if let Some(reason) = stopped {
+        report.push_str(&format!("\nStopped early, because {}.", reason));
+    }
+
+    match run.output {
+        Some(ref output) => {
+            pattern.to_file(output)?;
+            println!("{}", report);
+        }
+        None => {
+            print!("{}", rle::format_rle_file(&pattern));
+            eprintln!("{}", report);
+        }
+    }
+
+    Ok(())
+}
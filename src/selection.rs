@@ -0,0 +1,160 @@
+use super::rand::{self, Rng};
+use game_of_life::automaton::Automaton;
+use game_of_life::parsers::Pattern;
+use game_of_life::{Bounds, CellState};
+
+/// Selects a rectangle of cells with the mouse, to change it or to copy it to a clipboard that can
+/// be pasted elsewhere.
+#[derive(Default)]
+pub struct Selector {
+    pub enabled: bool,
+    pub selection: Option<Bounds>,
+    /// The cell where the selection started, while the mouse button is held.
+    anchor: Option<(isize, isize)>,
+    /// The cells that were copied or cut, relative to the top-left corner of the selection.
+    pub clipboard: Option<Pattern>,
+    /// Whether the clipboard follows the cursor, to be pasted with a click.
+    pub pasting: bool,
+}
+
+impl Selector {
+    /// Start or stop selecting. The selection is forgotten when selecting stops.
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+        self.anchor = None;
+        if !self.enabled {
+            self.selection = None;
+        }
+    }
+
+    /// Start selecting a rectangle at the given cell.
+    pub fn begin(&mut self, cell: (isize, isize)) {
+        self.anchor = Some(cell);
+        self.selection = Some((cell, cell));
+    }
+
+    /// Select the rectangle between the cell where the selection started and the given cell.
+    pub fn extend(&mut self, cell: (isize, isize)) {
+        if let Some(anchor) = self.anchor {
+            self.selection = Some((
+                (anchor.0.min(cell.0), anchor.1.min(cell.1)),
+                (anchor.0.max(cell.0), anchor.1.max(cell.1)),
+            ));
+        }
+    }
+
+    pub fn end(&mut self) {
+        self.anchor = None;
+    }
+
+    /// Whether a rectangle is being selected.
+    pub fn is_selecting(&self) -> bool {
+        self.anchor.is_some()
+    }
+
+    /// Copy the selected cells to the clipboard.
+    pub fn copy(&mut self, automaton: &dyn Automaton) {
+        if let Some(selection) = self.selection {
+            self.clipboard = Some(Pattern::from_automaton(automaton, selection));
+        }
+    }
+
+    /// Copy the selected cells to the clipboard and kill them.
+    pub fn cut(&mut self, automaton: &mut dyn Automaton) {
+        self.copy(automaton);
+        self.clear(automaton);
+    }
+
+    /// Kill the selected cells.
+    pub fn clear(&self, automaton: &mut dyn Automaton) {
+        self.set_each(automaton, &mut |_, _| CellState::Dead);
+    }
+
+    /// Make the selected cells alive.
+    pub fn fill(&self, automaton: &mut dyn Automaton) {
+        self.set_each(automaton, &mut |_, _| CellState::Alive);
+    }
+
+    /// Make every selected cell alive with the given chance, in percent, or dead otherwise.
+    pub fn randomise(&self, automaton: &mut dyn Automaton, chance: u8) {
+        let mut rng = rand::thread_rng();
+        self.set_each(automaton, &mut |_, _| {
+            (rng.gen::<u8>() % 100 <= chance).into()
+        });
+    }
+
+    /// Rotate the clipboard while pasting, or the selected cells otherwise, clockwise by 90
+    /// degrees.
+    pub fn rotate_clockwise(&mut self, automaton: &mut dyn Automaton) {
+        self.transform(automaton, Pattern::rotate_clockwise);
+    }
+
+    /// Mirror the clipboard while pasting, or the selected cells otherwise, from left to right.
+    pub fn flip_horizontally(&mut self, automaton: &mut dyn Automaton) {
+        self.transform(automaton, Pattern::flip_horizontally);
+    }
+
+    /// Mirror the clipboard while pasting, or the selected cells otherwise, from top to bottom.
+    pub fn flip_vertically(&mut self, automaton: &mut dyn Automaton) {
+        self.transform(automaton, Pattern::flip_vertically);
+    }
+
+    /// Start showing the clipboard at the cursor, if it is not empty.
+    pub fn start_pasting(&mut self) {
+        self.pasting = self.clipboard.is_some();
+    }
+
+    /// Set the cells of the clipboard, with its top-left corner at the given cell.
+    pub fn paste(&mut self, automaton: &mut dyn Automaton, cell: (isize, isize)) {
+        if let Some(ref clipboard) = self.clipboard {
+            clipboard.paste(automaton, cell);
+        }
+        self.pasting = false;
+    }
+
+    /// The clipboard, if it is being pasted.
+    pub fn ghost(&self) -> Option<&Pattern> {
+        if self.pasting {
+            self.clipboard.as_ref()
+        } else {
+            None
+        }
+    }
+
+    fn set_each(
+        &self,
+        automaton: &mut dyn Automaton,
+        f: &mut dyn FnMut(isize, isize) -> CellState,
+    ) {
+        if let Some(((min_x, min_y), (max_x, max_y))) = self.selection {
+            for y in min_y..max_y + 1 {
+                for x in min_x..max_x + 1 {
+                    automaton.set(x, y, f(x, y));
+                }
+            }
+        }
+    }
+
+    fn transform(&mut self, automaton: &mut dyn Automaton, f: fn(&mut Pattern)) {
+        if self.pasting {
+            if let Some(ref mut clipboard) = self.clipboard {
+                f(clipboard);
+            }
+        } else if let Some(selection) = self.selection {
+            let mut pattern = Pattern::from_automaton(automaton, selection);
+            self.clear(automaton);
+            f(&mut pattern);
+
+            let (width, height) = pattern.size();
+            let top_left = selection.0;
+            pattern.paste(automaton, top_left);
+            self.selection = Some((
+                top_left,
+                (
+                    top_left.0 + width as isize - 1,
+                    top_left.1 + height as isize - 1,
+                ),
+            ));
+        }
+    }
+}
@@ -1,6 +1,6 @@
 #version 330 core
-uniform vec4 color;
+in vec4 square_color;
 out vec4 fragment_color;
 void main() {
-    fragment_color = color;
+    fragment_color = square_color;
 }
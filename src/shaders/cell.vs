@@ -1,7 +1,12 @@
 #version 330 core
 layout (location = 0) in vec3 position;
-uniform mat4 scale;
-uniform mat4 translate;
+// The top-left corner, width and height of the square of a cell, with the y-axis pointing down.
+layout (location = 1) in vec4 square;
+layout (location = 2) in vec4 color;
+uniform mat4 projection;
+out vec4 square_color;
 void main() {
-    gl_Position = translate * scale * vec4(position, 1.0);
+    vec2 corner = square.xy + (position.xy + 1.0) / 2.0 * square.zw;
+    gl_Position = projection * vec4(corner, 0.0, 1.0);
+    square_color = color;
 }
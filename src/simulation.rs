@@ -0,0 +1,141 @@
+use super::playback::Playback;
+use game_of_life::GameOfLife;
+use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The game of life and how fast it is updated, which are shared between the window and the
+/// simulation thread.
+pub struct State {
+    pub game_of_life: GameOfLife,
+    pub playback: Playback,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    /// A copy of the most recent generation, which is rendered while the next ones are computed.
+    frame: Mutex<GameOfLife>,
+    /// Whether the game of life has changed since it was last copied to the frame.
+    changed: AtomicBool,
+    running: AtomicBool,
+}
+
+/// Updates a game of life on its own thread, so that slow generations do not stall the window
+/// and the number of generations per second is not limited by the frame rate.
+pub struct Simulation {
+    shared: Arc<Shared>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Simulation {
+    /// Start updating the game of life as fast as the playback says. The frame is replaced by the
+    /// newest generation at most once every `frame_delay`, which should be the time between two
+    /// rendered frames.
+    pub fn new(game_of_life: GameOfLife, playback: Playback, frame_delay: Duration) -> Self {
+        let mut frame = GameOfLife::new(game_of_life.width, game_of_life.height);
+        frame.copy_from(&game_of_life);
+
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                game_of_life,
+                playback,
+            }),
+            frame: Mutex::new(frame),
+            changed: AtomicBool::new(false),
+            running: AtomicBool::new(true),
+        });
+
+        let thread = {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || run(&shared, frame_delay))
+        };
+
+        Simulation {
+            shared,
+            thread: Some(thread),
+        }
+    }
+
+    /// Change the game of life. The frame shows the change once the simulation thread copies it,
+    /// within the time between two frames.
+    pub fn update<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut State) -> R,
+    {
+        let mut state = self.shared.state.lock().unwrap();
+        let result = f(&mut state);
+        self.shared.changed.store(true, Ordering::SeqCst);
+
+        result
+    }
+
+    /// Change the playback, without copying the game of life to the frame.
+    pub fn update_playback<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut Playback) -> R,
+    {
+        f(&mut self.shared.state.lock().unwrap().playback)
+    }
+
+    /// A description of the playback, as given by `Playback::describe`.
+    pub fn describe_playback(&self) -> String {
+        self.shared.state.lock().unwrap().playback.describe()
+    }
+
+    /// The most recent generation that has been computed.
+    /// The simulation thread waits with showing newer generations until the guard is dropped.
+    pub fn frame(&self) -> MutexGuard<'_, GameOfLife> {
+        self.shared.frame.lock().unwrap()
+    }
+}
+
+impl Drop for Simulation {
+    fn drop(&mut self) {
+        self.shared.running.store(false, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            thread.join().unwrap();
+        }
+    }
+}
+
+/// Update the game of life until the simulation is dropped.
+/// Once the game of life has changed and `frame_delay` has passed since the last copy, it is
+/// copied to a back buffer, which is then swapped with the frame.
+fn run(shared: &Shared, frame_delay: Duration) {
+    let (width, height) = {
+        let state = shared.state.lock().unwrap();
+        (state.game_of_life.width, state.game_of_life.height)
+    };
+    let mut back = GameOfLife::new(width, height);
+    let mut copied: Option<Instant> = None;
+
+    while shared.running.load(Ordering::SeqCst) {
+        let generations = shared.state.lock().unwrap().playback.due(Instant::now());
+
+        // Release the lock between generations, so the window can change the game of life.
+        for _ in 0..generations {
+            if !shared.running.load(Ordering::SeqCst) {
+                return;
+            }
+            shared.state.lock().unwrap().game_of_life.update();
+            shared.changed.store(true, Ordering::SeqCst);
+        }
+
+        let due = copied.is_none_or(|copied| copied.elapsed() >= frame_delay);
+        if due && shared.changed.load(Ordering::SeqCst) {
+            {
+                let state = shared.state.lock().unwrap();
+                shared.changed.store(false, Ordering::SeqCst);
+                back.copy_from(&state.game_of_life);
+            }
+            mem::swap(&mut *shared.frame.lock().unwrap(), &mut back);
+            copied = Some(Instant::now());
+        }
+
+        if generations == 0 {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
@@ -0,0 +1,319 @@
+use super::config::Config;
+use super::game_of_life::automaton::Automaton;
+use super::game_of_life::image::{self, Style};
+use super::game_of_life::GameOfLife;
+use super::playback::Playback;
+use super::simulation::Simulation;
+use super::termion;
+use super::termion::event::Key;
+use super::termion::input::TermRead;
+use super::termion::raw::IntoRawMode;
+use super::termion::screen::AlternateScreen;
+use super::termion::{clear, color, cursor};
+use super::view::View;
+use std::fmt::Write as FmtWrite;
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How far the I and O keys zoom in and out, in the units of `View::on_scroll`.
+const ZOOM_IN: f32 = 100.0;
+const ZOOM_OUT: f32 = -50.0;
+
+/// The characters that the cells are drawn with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Characters {
+    /// Braille characters, which have 2 by 4 dots in the color of the first dot that is lit.
+    Braille,
+    /// Upper half blocks, which split a character in an upper and a lower dot that each have their
+    /// own color.
+    HalfBlocks,
+}
+
+impl Characters {
+    /// The number of dots of a character, horizontally and vertically.
+    fn dots(self) -> (usize, usize) {
+        match self {
+            Characters::Braille => (2, 4),
+            Characters::HalfBlocks => (1, 2),
+        }
+    }
+
+    fn toggle(self) -> Self {
+        match self {
+            Characters::Braille => Characters::HalfBlocks,
+            Characters::HalfBlocks => Characters::Braille,
+        }
+    }
+}
+
+/// Show the game of life in the terminal until Q or Escape is pressed. Every dot of a character
+/// is a pixel of the view, so a cell width of 1 draws every cell as one dot.
+pub fn run(mut config: Config, game_of_life: GameOfLife) -> Result<(), String> {
+    let stdout = io::stdout();
+    let raw = stdout.lock().into_raw_mode().map_err(|e| e.to_string())?;
+    let mut screen = AlternateScreen::from(raw);
+    let mut keys = termion::async_stdin().keys();
+
+    let simulation = Simulation::new(
+        game_of_life,
+        Playback::from_config(&config),
+        config.frame_delay(),
+    );
+    let mut view = View::from_config(&config);
+    let mut characters = Characters::Braille;
+    // The size of the terminal and the characters that the view was last resized for.
+    let mut layout = None;
+    // The name of the file that is being typed after pressing L.
+    let mut prompt: Option<String> = None;
+    // An error that is shown until the next key is pressed.
+    let mut message: Option<String> = None;
+
+    let delay = config.frame_delay();
+
+    write!(screen, "{}", cursor::Hide).map_err(|e| e.to_string())?;
+    loop {
+        let frame_start = Instant::now();
+
+        // Keep the bottom line for the status.
+        let (columns, rows) = termion::terminal_size().map_err(|e| e.to_string())?;
+        let (columns, rows) = (columns as usize, (rows as usize).saturating_sub(1).max(1));
+        if layout != Some((columns, rows, characters)) {
+            let center = view.get_center();
+            let (dots_x, dots_y) = characters.dots();
+            view.on_resize((columns * dots_x) as f32, (rows * dots_y) as f32);
+            if layout.is_some() {
+                view.set_center(center);
+            }
+            layout = Some((columns, rows, characters));
+        }
+
+        let mut quit = false;
+        for key in keys.by_ref() {
+            let key = key.map_err(|e| e.to_string())?;
+            message = None;
+
+            if let Some(mut path) = prompt.take() {
+                match key {
+                    Key::Char('\n') => {
+                        let loaded = simulation.update(|state| {
+                            super::load_file(&mut state.game_of_life, &path, &config)
+                        });
+                        match loaded {
+                            Ok(()) => config.file = Some(path),
+                            Err(e) => message = Some(e),
+                        }
+                    }
+                    Key::Esc | Key::Ctrl('c') => (),
+                    Key::Backspace => {
+                        path.pop();
+                        prompt = Some(path);
+                    }
+                    Key::Char(c) => {
+                        path.push(c);
+                        prompt = Some(path);
+                    }
+                    _ => prompt = Some(path),
+                }
+                continue;
+            }
+
+            match key {
+                Key::Char('q') | Key::Esc | Key::Ctrl('c') => quit = true,
+                Key::Left | Key::Char('a') => {
+                    view.pan(-super::pan_distance(view.cells_on_width), 0.0)
+                }
+                Key::Right | Key::Char('d') => {
+                    view.pan(super::pan_distance(view.cells_on_width), 0.0)
+                }
+                Key::Up | Key::Char('w') => {
+                    view.pan(0.0, -super::pan_distance(view.cells_on_height))
+                }
+                Key::Down | Key::Char('s') => {
+                    view.pan(0.0, super::pan_distance(view.cells_on_height))
+                }
+                Key::Char('i') => zoom(&mut view, ZOOM_IN),
+                Key::Char('o') => zoom(&mut view, ZOOM_OUT),
+                Key::Char('z') => {
+                    // Zoom to fit the alive cells.
+                    if let Some(bounds) = simulation.frame().bounds() {
+                        view.zoom_to_fit(bounds);
+                    }
+                }
+                Key::Char('0') => view.reset(),
+                Key::Char('b') => characters = characters.toggle(),
+                Key::Char('l') => prompt = Some(String::new()),
+                Key::Char(' ') => {
+                    let reinitialised = simulation
+                        .update(|state| super::reinitialise(&mut state.game_of_life, &config));
+                    if let Err(e) = reinitialised {
+                        message = Some(e);
+                    }
+                }
+                Key::Char('p') => simulation.update_playback(|playback| playback.toggle_pause()),
+                Key::Char('.') => simulation.update_playback(|playback| playback.step()),
+                Key::Char('=') | Key::Char('+') => {
+                    simulation.update_playback(|playback| playback.faster())
+                }
+                Key::Char('-') => simulation.update_playback(|playback| playback.slower()),
+                Key::Char(',') => {
+                    simulation.update(|state| state.game_of_life.step_back());
+                }
+                Key::Home => simulation.update(|state| {
+                    if let Some(oldest) = state.game_of_life.history.oldest() {
+                        state.game_of_life.rewind(oldest).unwrap();
+                    }
+                }),
+                _ => (),
+            }
+        }
+        if quit {
+            break;
+        }
+
+        let playback = simulation.describe_playback();
+        let output = {
+            let frame = simulation.frame();
+            let status = match (&prompt, &message) {
+                (Some(path), _) => format!("Load file: {}", path),
+                (_, Some(message)) => message.clone(),
+                _ => super::window_title(&frame, &playback),
+            };
+            draw(
+                &config,
+                &view,
+                &*frame,
+                characters,
+                (columns, rows),
+                &status,
+            )
+        };
+        screen
+            .write_all(output.as_bytes())
+            .and_then(|_| screen.flush())
+            .map_err(|e| e.to_string())?;
+
+        let elapsed = frame_start.elapsed();
+        // Poll the keyboard now and then, even without a frame rate limit.
+        thread::sleep(delay.max(Duration::from_millis(1)).saturating_sub(elapsed));
+    }
+
+    write!(screen, "{}{}", color::Fg(color::Reset), cursor::Show).map_err(|e| e.to_string())
+}
+
+/// Zoom in or out on the center of the view.
+fn zoom(view: &mut View, y: f32) {
+    let (width, height) = (view.window_width, view.window_height);
+    view.on_scroll(y, f64::from(width) / 2.0, f64::from(height) / 2.0);
+}
+
+/// The characters and escape codes that draw the view of the automaton on a terminal of the given
+/// number of columns and rows, followed by a status line.
+fn draw(
+    config: &Config,
+    view: &View,
+    automaton: &dyn Automaton,
+    characters: Characters,
+    (columns, rows): (usize, usize),
+    status: &str,
+) -> String {
+    let viewport = view.viewport();
+    let style = Style {
+        // The edges of the view are not the edges of the board.
+        border: false,
+        ..config.style()
+    };
+    let image = image::render(automaton, &viewport, &style);
+    let background = image::to_rgb(style.background);
+
+    // The pixels of the image are shifted by the part of the top-left cell that is out of view.
+    let (offset_x, offset_y) = view.offset();
+    let (offset_x, offset_y) = (
+        (offset_x * view.cell_width) as usize,
+        (offset_y * view.cell_width) as usize,
+    );
+    let dot = |x: usize, y: usize| -> [u8; 3] {
+        let (x, y) = (x + offset_x, y + offset_y);
+        if x < image.width && y < image.height {
+            image.pixel(x, y)
+        } else {
+            background
+        }
+    };
+
+    let mut output = String::new();
+    let mut colors = Colors::default();
+    for row in 0..rows {
+        write!(output, "{}", cursor::Goto(1, row as u16 + 1)).unwrap();
+        for column in 0..columns {
+            match characters {
+                Characters::Braille => {
+                    let mut bits = 0;
+                    let mut lit = None;
+                    for (i, &(x, y)) in BRAILLE_DOTS.iter().enumerate() {
+                        let pixel = dot(column * 2 + x, row * 4 + y);
+                        if pixel != background {
+                            bits |= 1 << i;
+                            lit = lit.or(Some(pixel));
+                        }
+                    }
+                    colors.set(&mut output, lit.unwrap_or(background), background);
+                    output.push(::std::char::from_u32(0x2800 + bits).unwrap());
+                }
+                Characters::HalfBlocks => {
+                    let (upper, lower) = (dot(column, row * 2), dot(column, row * 2 + 1));
+                    colors.set(&mut output, upper, lower);
+                    output.push('▀');
+                }
+            }
+        }
+    }
+
+    let status: String = status.chars().take(columns).collect();
+    write!(
+        output,
+        "{}{}{}{}{}",
+        cursor::Goto(1, rows as u16 + 1),
+        color::Fg(color::Reset),
+        color::Bg(color::Reset),
+        clear::CurrentLine,
+        status
+    )
+    .unwrap();
+
+    output
+}
+
+/// The position of each dot of a braille character, in the order of the bits of its code point.
+const BRAILLE_DOTS: [(usize, usize); 8] = [
+    (0, 0),
+    (0, 1),
+    (0, 2),
+    (1, 0),
+    (1, 1),
+    (1, 2),
+    (0, 3),
+    (1, 3),
+];
+
+/// The colors that the terminal is drawing with, so that they are only changed when needed.
+#[derive(Default)]
+struct Colors {
+    foreground: Option<[u8; 3]>,
+    background: Option<[u8; 3]>,
+}
+
+impl Colors {
+    fn set(&mut self, output: &mut String, foreground: [u8; 3], background: [u8; 3]) {
+        if self.foreground != Some(foreground) {
+            let [r, g, b] = foreground;
+            write!(output, "{}", color::Fg(color::Rgb(r, g, b))).unwrap();
+            self.foreground = Some(foreground);
+        }
+        if self.background != Some(background) {
+            let [r, g, b] = background;
+            write!(output, "{}", color::Bg(color::Rgb(r, g, b))).unwrap();
+            self.background = Some(background);
+        }
+    }
+}
@@ -1,12 +1,20 @@
+use super::game_of_life::image::Viewport;
+use super::game_of_life::Bounds;
 use super::Config;
 
 pub struct View {
     pub cell_width: f32,
+    /// The cell width that was chosen by zooming, which is increased if the board would not fill
+    /// the window.
     base_cell_width: f32,
+    /// The cell width that the view starts with and is reset to.
+    default_cell_width: f32,
 
+    /// The top-left cell that is (partially) in view.
     pub y: usize,
     pub x: usize,
 
+    /// The position of the top-left corner of the window on the board, in cells.
     precise_y: f64,
     precise_x: f64,
 
@@ -28,23 +36,59 @@ impl View {
     }
 
     pub fn gl_cell_width(&self) -> f32 {
-        self.cell_width / self.window_width as f32 * 2.0
+        self.cell_width / self.window_width * 2.0
     }
 
     pub fn gl_cell_height(&self) -> f32 {
-        self.cell_width / self.window_height as f32 * 2.0
+        self.cell_width / self.window_height * 2.0
+    }
+
+    /// The part of the top-left cell that lies outside of the window, as a fraction of a cell.
+    pub fn offset(&self) -> (f32, f32) {
+        (
+            (self.precise_x - self.x as f64) as f32,
+            (self.precise_y - self.y as f64) as f32,
+        )
+    }
+
+    /// The cells that are (partially) in view, and the width they are drawn at.
+    pub fn viewport(&self) -> Viewport {
+        let region = (
+            (self.x as isize, self.y as isize),
+            (
+                (self.x + self.cells_on_width) as isize - 1,
+                (self.y + self.cells_on_height) as isize - 1,
+            ),
+        );
+
+        Viewport::new(region, self.cell_width)
+    }
+
+    /// The position on the board of the cell under a position in the window, given in the same
+    /// units as the window size. With `skewed`, rows are shifted like `render::Square::skewed`.
+    pub fn cell_at(&self, window_x: f64, window_y: f64, skewed: bool) -> (isize, isize) {
+        let (board_x, board_y) = self.board_position(window_x, window_y);
+        let board_y = board_y.floor();
+        let board_x = if skewed {
+            let rows_below_middle = board_y - self.y as f64 - self.cells_on_height as f64 / 2.0;
+            board_x + rows_below_middle / 2.0
+        } else {
+            board_x
+        };
+
+        (board_x.floor() as isize, board_y as isize)
     }
 
     pub fn determine_window_size(&mut self, screen_width: f32, screen_height: f32) {
         self.window_width = if self.board_width as f32 * self.cell_width > screen_width {
             screen_width
         } else {
-            (self.board_width as f32 * self.cell_width)
+            self.board_width as f32 * self.cell_width
         };
         self.window_height = if self.board_height as f32 * self.cell_width > screen_height {
             screen_height
         } else {
-            (self.board_height as f32 * self.cell_width)
+            self.board_height as f32 * self.cell_width
         };
 
         let (width, height) = (self.window_width, self.window_height);
@@ -68,8 +112,8 @@ impl View {
         let window_width = 0.0;
         let window_height = 0.0;
 
-        let cells_on_width = (window_width as f32 / cell_width).ceil() as usize;
-        let cells_on_height = (window_height as f32 / cell_width).ceil() as usize;
+        let cells_on_width = (window_width / cell_width).ceil() as usize;
+        let cells_on_height = (window_height / cell_width).ceil() as usize;
 
         Self {
             y: 0,
@@ -80,6 +124,7 @@ impl View {
 
             cell_width,
             base_cell_width,
+            default_cell_width: base_cell_width,
 
             capture_cursor: false,
 
@@ -98,76 +143,58 @@ impl View {
         self.window_width = width;
         self.window_height = height;
 
-        // Reset to the base cell width.
-        self.cell_width = self.base_cell_width;
-
-        self.cells_on_width = (self.window_width / self.cell_width).ceil() as usize;
-        self.cells_on_height = (self.window_height / self.cell_width).ceil() as usize;
-
-        if self.cells_on_width > self.board_width {
-            self.cell_width = self.window_width / self.board_width as f32;
-            self.cells_on_width = (self.window_width / self.cell_width) as usize;
-            self.cells_on_height = (self.window_height / self.cell_width) as usize;
-        }
+        // Use the base cell width, unless the board would not fill the window.
+        self.cell_width = self
+            .base_cell_width
+            .max(self.window_width / self.board_width as f32)
+            .max(self.window_height / self.board_height as f32);
 
-        if self.cells_on_height > self.board_height {
-            self.cell_width = self.window_height / self.board_height as f32;
-            self.cells_on_height = (self.window_height / self.cell_width) as usize;
-        }
-
-        // Trigger function to check for moving outside of the board.
-        self.on_mouse_move(0.0, 0.0);
+        self.clamp();
     }
 
     pub fn on_mouse_move(&mut self, mouse_x: f64, mouse_y: f64) {
         if self.capture_cursor {
-            // Prevent y from moving outside of the board and update it.
-            if self.precise_y - mouse_y < 0.0 {
-                self.precise_y = 0.0;
-            } else if self.precise_y - mouse_y + self.cells_on_height as f64
-                > self.board_height as f64
-            {
-                self.precise_y = (self.board_height - self.cells_on_height) as f64;
-            } else {
-                self.precise_y -= mouse_y;
-            }
-
-            // Prevent x from moving outside of the board and update it.
-            if self.precise_x - mouse_x < 0.0 {
-                self.precise_x = 0.0;
-            } else if self.precise_x - mouse_x + self.cells_on_width as f64
-                > self.board_width as f64
-            {
-                self.precise_x = (self.board_width - self.cells_on_width) as f64;
-            } else {
-                self.precise_x -= mouse_x;
-            }
-
-            self.y = self.precise_y as usize;
-            self.x = self.precise_x as usize;
+            self.precise_x -= mouse_x;
+            self.precise_y -= mouse_y;
+            self.clamp();
         }
     }
 
-    // pub fn get_center(&self) -> (f32, f32) {
-    //     let x = self.cells_on_width as f32 / 2.0 + self.precise_x as f32;
-    //     let y = self.cells_on_height as f32 / 2.0 + self.precise_y as f32;
-
-    //     (x, y)
-    // }
+    /// Move the view by the given number of cells.
+    pub fn pan(&mut self, cells_x: f64, cells_y: f64) {
+        self.precise_x += cells_x;
+        self.precise_y += cells_y;
+        self.clamp();
+    }
 
-    // pub fn set_center(&mut self, coordinates: (f32, f32)) {
-    //     let (x, y) = coordinates;
-    //     let (x, y) = (x as f64, y as f64);
+    /// Move the board along with the cursor, when it is dragged by the given distance in the
+    /// window.
+    pub fn drag(&mut self, window_x: f64, window_y: f64) {
+        let cell_width = f64::from(self.cell_width);
+        self.pan(-window_x / cell_width, -window_y / cell_width);
+    }
 
-    //     self.precise_x = x - self.cells_on_width as f64 / 2.0;
-    //     self.precise_y = y - self.cells_on_height as f64 / 2.0;
+    /// The position on the board at the center of the window, in cells.
+    pub fn get_center(&self) -> (f64, f64) {
+        self.board_position(
+            f64::from(self.window_width) / 2.0,
+            f64::from(self.window_height) / 2.0,
+        )
+    }
 
-    //     self.y = self.precise_y as usize;
-    //     self.x = self.precise_x as usize;
-    // }
+    /// Move the view so that the given position on the board, in cells, is at the center of the
+    /// window.
+    pub fn set_center(&mut self, center: (f64, f64)) {
+        let cell_width = f64::from(self.cell_width);
+        self.precise_x = center.0 - f64::from(self.window_width) / cell_width / 2.0;
+        self.precise_y = center.1 - f64::from(self.window_height) / cell_width / 2.0;
+        self.clamp();
+    }
 
-    pub fn on_scroll(&mut self, y: f32) {
-        // let center = self.get_center();
+    /// Zoom in or out by `y` percent, keeping the cell under the given position in the window in
+    /// place.
+    pub fn on_scroll(&mut self, y: f32, window_x: f64, window_y: f64) {
+        let anchor = self.board_position(window_x, window_y);
         self.base_cell_width += self.base_cell_width * y * 0.01;
 
         // Check if zooming further out is possible
@@ -188,9 +215,70 @@ impl View {
 
         let width = self.window_width;
         let height = self.window_height;
+        self.on_resize(width, height);
+
+        let cell_width = f64::from(self.cell_width);
+        self.precise_x = anchor.0 - window_x / cell_width;
+        self.precise_y = anchor.1 - window_y / cell_width;
+        self.clamp();
+    }
 
+    /// Zoom so that the given cells fill the window, and center them.
+    pub fn zoom_to_fit(&mut self, bounds: Bounds) {
+        let ((min_x, min_y), (max_x, max_y)) = bounds;
+        let (width, height) = ((max_x - min_x + 1) as f32, (max_y - min_y + 1) as f32);
+        self.base_cell_width = (self.window_width / width)
+            .min(self.window_height / height)
+            .min(self.window_width)
+            .min(self.window_height);
+
+        let (window_width, window_height) = (self.window_width, self.window_height);
+        self.on_resize(window_width, window_height);
+        self.set_center((
+            (min_x + max_x + 1) as f64 / 2.0,
+            (min_y + max_y + 1) as f64 / 2.0,
+        ));
+    }
+
+    /// Go back to the cell width of the config, at the top-left corner of the board.
+    pub fn reset(&mut self) {
+        self.base_cell_width = self.default_cell_width;
+        self.precise_x = 0.0;
+        self.precise_y = 0.0;
+
+        let (width, height) = (self.window_width, self.window_height);
         self.on_resize(width, height);
+    }
+
+    /// The position on the board under a position in the window, in cells.
+    fn board_position(&self, window_x: f64, window_y: f64) -> (f64, f64) {
+        let cell_width = f64::from(self.cell_width);
+        (
+            self.precise_x + window_x / cell_width,
+            self.precise_y + window_y / cell_width,
+        )
+    }
 
-        // self.set_center(center);
+    /// Prevent the view from moving outside of the board, and update the cells in view.
+    fn clamp(&mut self) {
+        let cell_width = f64::from(self.cell_width);
+        let window_cells_x = f64::from(self.window_width) / cell_width;
+        let window_cells_y = f64::from(self.window_height) / cell_width;
+
+        let max_x = (self.board_width as f64 - window_cells_x).max(0.0);
+        let max_y = (self.board_height as f64 - window_cells_y).max(0.0);
+        self.precise_x = self.precise_x.max(0.0).min(max_x);
+        self.precise_y = self.precise_y.max(0.0).min(max_y);
+
+        self.x = self.precise_x as usize;
+        self.y = self.precise_y as usize;
+
+        // Include the cells that are partially in view.
+        let end_x =
+            ((self.precise_x + window_cells_x - 1e-6).ceil() as usize).min(self.board_width);
+        let end_y =
+            ((self.precise_y + window_cells_y - 1e-6).ceil() as usize).min(self.board_height);
+        self.cells_on_width = end_x.saturating_sub(self.x);
+        self.cells_on_height = end_y.saturating_sub(self.y);
     }
 }
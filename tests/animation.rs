@@ -0,0 +1,92 @@
+extern crate game_of_life;
+extern crate gif;
+use game_of_life::animation::{record, Animation};
+use game_of_life::automaton::Automaton;
+use game_of_life::image::{render, Image, Style, Viewport};
+use game_of_life::{Bounds, CellState, GameOfLife};
+use std::time::Duration;
+
+const REGION: Bounds = ((0, 0), (5, 5));
+
+/// Decode every frame of a GIF, as red, green and blue components, with the delay of the frame.
+fn decode(bytes: &[u8]) -> Vec<(Vec<u8>, u16)> {
+    use gif::SetParameter;
+
+    let mut decoder = gif::Decoder::new(bytes);
+    decoder.set(gif::ColorOutput::RGBA);
+    let mut reader = decoder.read_info().unwrap();
+
+    let mut frames = Vec::new();
+    while let Some(frame) = reader.read_next_frame().unwrap() {
+        let rgb = frame
+            .buffer
+            .chunks(4)
+            .flat_map(|pixel| pixel[..3].to_vec())
+            .collect();
+        frames.push((rgb, frame.delay));
+    }
+    frames
+}
+
+/// Record the first generations of a board, and check the frames against the images of the same
+/// generations.
+fn check_recording(mut game_of_life: GameOfLife, cell_width: f32, style: &Style, frames: u64) {
+    let viewport = Viewport::new(REGION, cell_width);
+    let mut bytes = Vec::new();
+    let mut recorded = GameOfLife::new(6, 6);
+    recorded.copy_from(&game_of_life);
+    record(
+        &mut recorded,
+        &viewport,
+        style,
+        frames,
+        Duration::from_millis(120),
+        &mut bytes,
+    )
+    .unwrap();
+    assert_eq!(recorded.generation(), frames - 1);
+
+    let decoded = decode(&bytes);
+    assert_eq!(decoded.len() as u64, frames);
+    for (pixels, delay) in decoded {
+        assert_eq!(pixels, render(&game_of_life, &viewport, style).bytes());
+        assert_eq!(delay, 12);
+        game_of_life.step();
+    }
+}
+
+#[test]
+fn test_animation_record() {
+    let mut game_of_life = GameOfLife::new(6, 6);
+    for x in 1..4 {
+        game_of_life.set(x, 2, CellState::Alive);
+    }
+
+    let style = Style {
+        foreground: [0.2, 0.4, 0.6, 1.0],
+        border: true,
+        ..Style::default()
+    };
+    check_recording(game_of_life, 3.0, &style, 4);
+}
+
+#[test]
+fn test_animation_record_multi_state() {
+    // Brian's Brain, where cells are dying for one generation.
+    let mut game_of_life = GameOfLife::new(6, 6);
+    game_of_life.with_rule("B2/S/C3".parse().unwrap());
+    game_of_life.set(2, 2, CellState::Alive);
+    game_of_life.set(3, 2, CellState::Alive);
+
+    check_recording(game_of_life, 2.0, &Style::default(), 3);
+}
+
+#[test]
+fn test_animation_frame_size() {
+    let mut bytes = Vec::new();
+    let mut animation = Animation::new(&mut bytes, 4, 3, Duration::from_millis(50)).unwrap();
+    assert!(animation.push(&Image::new(4, 3, [0, 0, 0])).is_ok());
+    assert!(animation.push(&Image::new(3, 4, [0, 0, 0])).is_err());
+
+    assert!(Animation::new(Vec::new(), 70_000, 1, Duration::from_millis(50)).is_err());
+}
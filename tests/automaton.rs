@@ -0,0 +1,99 @@
+extern crate game_of_life;
+use game_of_life::automaton::*;
+use game_of_life::hashlife::HashLife;
+use game_of_life::rule::Rule;
+use game_of_life::sparse::SparseLife;
+use game_of_life::{CellState, GameOfLife};
+
+const GLIDER: [(isize, isize); 5] = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+
+/// Put a glider on the universe and run it for eight generations.
+fn run_glider(automaton: &mut dyn Automaton) {
+    for &(x, y) in &GLIDER {
+        automaton.set(x + 10, y + 10, CellState::Alive);
+    }
+    automaton.step();
+    automaton.step_n(7);
+}
+
+#[test]
+fn test_automaton_engines_agree() {
+    let mut game_of_life = GameOfLife::new(40, 40);
+    let mut sparse_life = SparseLife::new(Rule::conway()).unwrap();
+    let mut hashlife = HashLife::new(Rule::conway()).unwrap();
+
+    let engines: [&mut dyn Automaton; 3] = [&mut game_of_life, &mut sparse_life, &mut hashlife];
+    for automaton in engines {
+        run_glider(automaton);
+
+        assert_eq!(automaton.generation(), 8);
+        assert_eq!(automaton.population(), 5);
+        assert_eq!(automaton.bounds(), Some(((12, 12), (14, 14))));
+
+        let mut cells = automaton.alive_cells(((0, 0), (39, 39)));
+        cells.sort();
+        let mut expected: Vec<_> = GLIDER.iter().map(|&(x, y)| (x + 12, y + 12)).collect();
+        expected.sort();
+        assert_eq!(cells, expected);
+
+        // Only the cells in the region are returned.
+        assert_eq!(automaton.alive_cells(((13, 13), (14, 14))).len(), 3);
+        assert_eq!(automaton.alive_cells(((0, 0), (12, 12))).len(), 0);
+    }
+}
+
+#[test]
+fn test_automaton_game_of_life_edges() {
+    let mut game_of_life = GameOfLife::new(70, 3);
+    game_of_life.set(-1, 0, CellState::Alive);
+    game_of_life.set(70, 0, CellState::Alive);
+    assert_eq!(game_of_life.population(), 0);
+    assert_eq!(game_of_life.get(-1, 0), CellState::Dead);
+
+    game_of_life.set(69, 2, CellState::Alive);
+    game_of_life.set(0, 1, CellState::Alive);
+    assert_eq!(game_of_life.bounds(), Some(((0, 1), (69, 2))));
+    assert_eq!(game_of_life.alive_cells(((-5, -5), (100, 100))).len(), 2);
+}
+
+#[test]
+fn test_automaton_block_populations() {
+    let mut game_of_life = GameOfLife::new(200, 150);
+    let mut sparse_life = SparseLife::new(Rule::conway()).unwrap();
+    let mut hashlife = HashLife::new(Rule::conway()).unwrap();
+
+    // A reproducible soup of cells.
+    let mut state: u64 = 1;
+    for y in 0..150 {
+        for x in 0..200 {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            if state >> 62 == 0 {
+                game_of_life.set(x, y, CellState::Alive);
+                sparse_life.set(x, y, CellState::Alive);
+                hashlife.set(x, y, CellState::Alive);
+            }
+        }
+    }
+
+    for &(region, block) in &[
+        (((0, 0), (199, 149)), 1),
+        (((0, 0), (199, 149)), 16),
+        (((-7, 3), (150, 160)), 13),
+        (((5, 5), (70, 9)), 64),
+    ] {
+        let (columns, rows) = blocks(region, block);
+        let ((min_x, min_y), _) = region;
+        let mut expected = vec![0; columns * rows];
+        for (x, y) in game_of_life.alive_cells(region) {
+            let (column, row) = ((x - min_x) as usize / block, (y - min_y) as usize / block);
+            expected[row * columns + column] += 1;
+        }
+
+        let engines: [&dyn Automaton; 3] = [&game_of_life, &sparse_life, &hashlife];
+        for automaton in engines {
+            assert_eq!(automaton.block_populations(region, block), expected);
+        }
+    }
+}
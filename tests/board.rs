@@ -0,0 +1,140 @@
+extern crate game_of_life;
+use game_of_life::board::*;
+use game_of_life::rule::Rule;
+use game_of_life::topology::Topology;
+use game_of_life::{CellState, GameOfLife};
+
+/// Fill a board with a reproducible pseudo-random soup.
+fn soup(game_of_life: &mut GameOfLife, seed: u64) {
+    let mut state = seed;
+    for y in 0..game_of_life.height {
+        for x in 0..game_of_life.width {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            game_of_life.board.set(x, y, (state >> 61 < 3).into());
+        }
+    }
+}
+
+/// Update a board one cell at a time.
+fn naive_update(game_of_life: &GameOfLife) -> Board {
+    let (width, height) = (game_of_life.width, game_of_life.height);
+    let mut next = Board::new(width, height);
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut neighbours = 0;
+            for i in -1..2 {
+                for j in -1..2 {
+                    if i == 0 && j == 0 {
+                        continue;
+                    }
+
+                    let neighbour =
+                        game_of_life
+                            .topology
+                            .map(x as isize + j, y as isize + i, width, height);
+                    if let Some((j, i)) = neighbour {
+                        if game_of_life.board.get(j, i) == CellState::Alive {
+                            neighbours += 1;
+                        }
+                    }
+                }
+            }
+
+            let alive = if game_of_life.board.get(x, y) == CellState::Alive {
+                game_of_life.rule.survival[neighbours]
+            } else {
+                game_of_life.rule.birth[neighbours]
+            };
+            next.set(x, y, alive.into());
+        }
+    }
+
+    next
+}
+
+#[test]
+fn test_board_get_set() {
+    let mut board = Board::new(100, 3);
+    assert_eq!(board.words_per_row(), 2);
+
+    board.set(0, 0, CellState::Alive);
+    board.set(99, 2, CellState::Alive);
+    board.set(64, 1, CellState::Alive);
+    board.set(64, 1, CellState::Dead);
+    assert_eq!(board.get(0, 0), CellState::Alive);
+    assert_eq!(board.get(99, 2), CellState::Alive);
+    assert_eq!(board.get(64, 1), CellState::Dead);
+    assert_eq!(board.population(), 2);
+
+    board.clear();
+    assert_eq!(board.population(), 0);
+}
+
+#[test]
+fn test_board_update_matches_naive_update() {
+    let topologies = [
+        Topology::Plane,
+        Topology::Torus,
+        Topology::KleinBottle,
+        Topology::CrossSurface,
+    ];
+    let rules = ["B3/S23", "B36/S23", "B3678/S34678", "B0123/S5678"];
+
+    for &(width, height) in &[(1, 1), (5, 3), (63, 4), (64, 5), (65, 6), (130, 7)] {
+        for &topology in &topologies {
+            for rule in &rules {
+                let mut game_of_life = GameOfLife::new(width, height);
+                game_of_life.with_topology(topology).unwrap();
+                game_of_life.with_rule(rule.parse::<Rule>().unwrap());
+                soup(&mut game_of_life, (width * height) as u64);
+
+                for _ in 0..4 {
+                    let expected = naive_update(&game_of_life);
+                    game_of_life.update();
+                    assert_eq!(
+                        game_of_life.board, expected,
+                        "{}x{} board on a {} with rule {}",
+                        width, height, topology, rule
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_board_update_sphere() {
+    let mut game_of_life = GameOfLife::new(70, 70);
+    game_of_life.with_topology(Topology::Sphere).unwrap();
+    soup(&mut game_of_life, 7);
+
+    for _ in 0..4 {
+        let expected = naive_update(&game_of_life);
+        game_of_life.update();
+        assert_eq!(game_of_life.board, expected);
+    }
+}
+
+#[test]
+fn test_board_update_after_copy_from() {
+    // Copying a board of a different size or rule must leave a game of life that updates the same
+    // way as the original.
+    for rule in &["B3/S23", "B2/S/C3", "B36/S23/C5"] {
+        let mut game_of_life = GameOfLife::new(70, 9);
+        game_of_life.with_rule(rule.parse::<Rule>().unwrap());
+        soup(&mut game_of_life, 3);
+        game_of_life.update();
+
+        let mut copy = GameOfLife::new(5, 130);
+        copy.update();
+        copy.copy_from(&game_of_life);
+        for _ in 0..3 {
+            game_of_life.update();
+            copy.update();
+            assert_eq!(copy.board, game_of_life.board, "rule {}", rule);
+        }
+    }
+}
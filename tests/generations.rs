@@ -0,0 +1,101 @@
+extern crate game_of_life;
+use game_of_life::automaton::Automaton;
+use game_of_life::board::Board;
+use game_of_life::rule::Rule;
+use game_of_life::{CellState, GameOfLife};
+
+/// Fill a board with a reproducible pseudo-random soup of alive, dying and dead cells.
+fn soup(game_of_life: &mut GameOfLife, seed: u64) {
+    let states = game_of_life.rule.states;
+    let mut state = seed;
+    for y in 0..game_of_life.height {
+        for x in 0..game_of_life.width {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            let cell = (state >> 56) as u8 % (states + 1);
+            let cell = if cell >= states { 0 } else { cell };
+            game_of_life.board.set(x, y, cell.into());
+        }
+    }
+}
+
+/// Update a board on a plane one cell at a time.
+fn naive_update(game_of_life: &GameOfLife) -> Board {
+    let (width, height) = (game_of_life.width, game_of_life.height);
+    let rule = &game_of_life.rule;
+    let mut next = Board::new(width, height);
+    next.set_multi_state(true);
+
+    for y in 0..height as isize {
+        for x in 0..width as isize {
+            let neighbours = (-1..2)
+                .flat_map(|i| (-1..2).map(move |j| (x + j, y + i)))
+                .filter(|&position| position != (x, y))
+                .filter(|&(j, i)| game_of_life.get(j, i) == CellState::Alive)
+                .count();
+
+            let state = u8::from(game_of_life.get(x, y));
+            let next_state = match state {
+                0 if rule.birth[neighbours] => 1,
+                0 => 0,
+                1 if rule.survival[neighbours] => 1,
+                _ if state + 1 < rule.states => state + 1,
+                _ => 0,
+            };
+            next.set(x as usize, y as usize, next_state.into());
+        }
+    }
+
+    next
+}
+
+#[test]
+fn test_generations_cell_state() {
+    for state in 0..=255 {
+        assert_eq!(u8::from(CellState::from(state)), state);
+    }
+    assert_eq!(CellState::from(2), CellState::Dying(1));
+    assert!(!bool::from(CellState::Dying(1)));
+}
+
+#[test]
+fn test_generations_brians_brain() {
+    // Two alive cells next to each other give birth to the cells on both sides of them, while they
+    // become dying.
+    let mut game_of_life = GameOfLife::new(6, 6);
+    game_of_life.with_rule(Rule::brians_brain());
+    game_of_life.board.set(2, 2, CellState::Alive);
+    game_of_life.board.set(2, 3, CellState::Alive);
+
+    game_of_life.update();
+    assert_eq!(game_of_life.board.get(2, 2), CellState::Dying(1));
+    assert_eq!(game_of_life.board.get(2, 3), CellState::Dying(1));
+    assert_eq!(game_of_life.board.get(1, 2), CellState::Alive);
+    assert_eq!(game_of_life.board.get(3, 3), CellState::Alive);
+    assert_eq!(game_of_life.population(), 4);
+
+    game_of_life.update();
+    assert_eq!(game_of_life.board.get(2, 2), CellState::Dead);
+}
+
+#[test]
+fn test_generations_update_matches_naive_update() {
+    for rule in &["B2/S/C3", "345/2/4", "B3/S23/C8", "B0/S8/C5"] {
+        for &(width, height) in &[(1, 1), (5, 3), (64, 4), (70, 6)] {
+            let mut game_of_life = GameOfLife::new(width, height);
+            game_of_life.with_rule(rule.parse().unwrap());
+            soup(&mut game_of_life, (width * height) as u64);
+
+            for _ in 0..4 {
+                let expected = naive_update(&game_of_life);
+                game_of_life.update();
+                assert_eq!(
+                    game_of_life.board, expected,
+                    "{}x{} board with rule {}",
+                    width, height, rule
+                );
+            }
+        }
+    }
+}
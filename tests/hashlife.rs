@@ -0,0 +1,127 @@
+extern crate game_of_life;
+use game_of_life::automaton::Automaton;
+use game_of_life::hashlife::*;
+use game_of_life::parsers::Pattern;
+use game_of_life::rule::Rule;
+use game_of_life::{CellState, GameOfLife};
+
+const GLIDER: [(isize, isize); 5] = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+
+#[test]
+fn test_hashlife_get_set() {
+    let mut hashlife = HashLife::new(Rule::conway()).unwrap();
+    assert_eq!(hashlife.population(), 0);
+    assert_eq!(hashlife.bounds(), None);
+
+    hashlife.set(-100, 3, CellState::Alive);
+    hashlife.set(1000, -7, CellState::Alive);
+    assert_eq!(hashlife.get(-100, 3), CellState::Alive);
+    assert_eq!(hashlife.get(1000, -7), CellState::Alive);
+    assert_eq!(hashlife.get(0, 0), CellState::Dead);
+    assert_eq!(hashlife.population(), 2);
+    assert_eq!(hashlife.bounds(), Some(((-100, -7), (1000, 3))));
+
+    hashlife.set(1000, -7, CellState::Dead);
+    assert_eq!(hashlife.population(), 1);
+
+    // Cells too far out for the universe to fit in `isize` coordinates are left dead.
+    hashlife.set(isize::MAX, isize::MIN, CellState::Alive);
+    assert_eq!(hashlife.get(isize::MAX, isize::MIN), CellState::Dead);
+    assert_eq!(hashlife.population(), 1);
+    assert_eq!(hashlife.get(-100, 3), CellState::Alive);
+
+    assert!(HashLife::new("B0/S".parse().unwrap()).is_err());
+
+    // Only alive and dead cells are stored.
+    let pattern = Pattern {
+        cells: vec![(0, 0), (1, 0)],
+        states: vec![1, 2],
+        ..Pattern::default()
+    };
+    assert!(HashLife::from_pattern(&pattern).is_err());
+}
+
+#[test]
+fn test_hashlife_glider() {
+    let mut hashlife = HashLife::new(Rule::conway()).unwrap();
+    for &(x, y) in &GLIDER {
+        hashlife.set(x, y, CellState::Alive);
+    }
+
+    // A glider moves one cell diagonally every four generations.
+    hashlife.step_pow2(20).unwrap();
+    assert_eq!(hashlife.generation(), 1 << 20);
+    assert_eq!(hashlife.population(), 5);
+    let distance = 1 << 18;
+    for &(x, y) in &GLIDER {
+        assert_eq!(hashlife.get(x + distance, y + distance), CellState::Alive);
+    }
+}
+
+#[test]
+fn test_hashlife_matches_game_of_life() {
+    let pattern = Pattern::from_file("./examples/lidka.cells").unwrap();
+    let mut hashlife = HashLife::from_pattern(&pattern).unwrap();
+
+    let size = 400;
+    let mut game_of_life = GameOfLife::new(size, size);
+    for &(x, y) in &pattern.cells {
+        let (x, y) = (x + size as isize / 2, y + size as isize / 2);
+        game_of_life
+            .board
+            .set(x as usize, y as usize, CellState::Alive);
+    }
+
+    for &generations in &[1, 2, 5, 100, 137] {
+        hashlife.step_n(generations);
+        for _ in 0..generations {
+            game_of_life.update();
+        }
+
+        assert_eq!(
+            hashlife.population(),
+            game_of_life.board.population() as u64
+        );
+        for y in 0..size {
+            for x in 0..size {
+                let position = (
+                    x as isize - size as isize / 2,
+                    y as isize - size as isize / 2,
+                );
+                assert_eq!(
+                    hashlife.get(position.0, position.1),
+                    game_of_life.board.get(x, y)
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_hashlife_step_limit() {
+    let mut hashlife = HashLife::new(Rule::conway()).unwrap();
+    for &(x, y) in &GLIDER {
+        hashlife.set(x, y, CellState::Alive);
+    }
+    assert!(hashlife.step_pow2(MAX_STEP_POW2 + 1).is_err());
+    assert_eq!(hashlife.generation(), 0);
+
+    // A glider keeps moving, until its position no longer fits in `isize` coordinates.
+    let mut steps = 0;
+    while hashlife.step_pow2(MAX_STEP_POW2).is_ok() {
+        steps += 1;
+    }
+    assert!(steps > 0);
+    assert_eq!(hashlife.generation(), steps << MAX_STEP_POW2);
+    assert_eq!(hashlife.population(), 5);
+
+    // A block never grows, so it can be advanced by any number of generations.
+    let mut hashlife = HashLife::new(Rule::conway()).unwrap();
+    for &(x, y) in &[(0, 0), (1, 0), (0, 1), (1, 1)] {
+        hashlife.set(x, y, CellState::Alive);
+    }
+    hashlife.step_n(u64::MAX);
+    assert_eq!(hashlife.generation(), u64::MAX);
+    assert_eq!(hashlife.population(), 4);
+    assert_eq!(hashlife.bounds(), Some(((0, 0), (1, 1))));
+}
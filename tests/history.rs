@@ -0,0 +1,91 @@
+extern crate game_of_life;
+use game_of_life::board::Board;
+use game_of_life::history::History;
+use game_of_life::rule::Rule;
+use game_of_life::GameOfLife;
+
+#[test]
+fn test_history_step_back() {
+    let mut game_of_life = GameOfLife::new(40, 40);
+    game_of_life.with_history(100).init_randomly(40);
+
+    let mut boards = vec![game_of_life.board.clone()];
+    for _ in 0..70 {
+        game_of_life.update();
+        boards.push(game_of_life.board.clone());
+    }
+
+    for generation in (0..70).rev() {
+        assert!(game_of_life.step_back());
+        assert_eq!(game_of_life.generation, generation);
+        assert_eq!(game_of_life.board, boards[generation as usize]);
+    }
+    assert!(!game_of_life.step_back());
+}
+
+#[test]
+fn test_history_rewind_and_resume() {
+    let mut game_of_life = GameOfLife::new(30, 30);
+    game_of_life.with_history(10).init_randomly(40);
+    game_of_life.update();
+    game_of_life.update();
+    let board = game_of_life.board.clone();
+    for _ in 0..48 {
+        game_of_life.update();
+    }
+
+    // Only the last 10 generations are kept.
+    assert_eq!(game_of_life.history.oldest(), Some(40));
+    assert_eq!(game_of_life.history.newest(), Some(49));
+    assert!(game_of_life.rewind(39).is_err());
+    let expected = game_of_life.history.get(45).unwrap();
+
+    game_of_life.rewind(43).unwrap();
+    assert_eq!(game_of_life.history.newest(), Some(42));
+    game_of_life.update();
+    game_of_life.update();
+    assert_eq!(game_of_life.generation, 45);
+    assert_eq!(game_of_life.board, expected);
+
+    game_of_life.init_randomly(40);
+    assert!(game_of_life.history.is_empty());
+    assert_ne!(game_of_life.board, board);
+}
+
+#[test]
+fn test_history_multi_state() {
+    let mut game_of_life = GameOfLife::new(30, 30);
+    game_of_life
+        .with_rule(Rule::brians_brain())
+        .with_history(50)
+        .init_randomly(40);
+
+    let mut boards = Vec::new();
+    for _ in 0..40 {
+        game_of_life.update();
+        boards.push(game_of_life.board.clone());
+    }
+
+    game_of_life.rewind(5).unwrap();
+    assert_eq!(game_of_life.board, boards[4]);
+}
+
+#[test]
+fn test_history_capacity() {
+    let mut history = History::new(0);
+    history.push(0, &Board::new(5, 5));
+    assert!(history.is_empty());
+
+    let mut history = History::new(3);
+    for generation in 0..5 {
+        history.push(generation, &Board::new(5, 5));
+    }
+    assert_eq!(history.len(), 3);
+    assert_eq!(history.get(2), Some(Board::new(5, 5)));
+    assert_eq!(history.get(1), None);
+
+    // A generation that does not follow the newest one starts a new history.
+    history.push(10, &Board::new(5, 5));
+    assert_eq!(history.len(), 1);
+    assert_eq!(history.oldest(), Some(10));
+}
@@ -0,0 +1,135 @@
+extern crate game_of_life;
+extern crate png;
+use game_of_life::automaton::Automaton;
+use game_of_life::image::{alive_blocks, render, Block, Image, Shading, Style, Viewport};
+use game_of_life::rule::Rule;
+use game_of_life::sparse::SparseLife;
+use game_of_life::{CellState, GameOfLife};
+
+const BLACK: [u8; 3] = [0, 0, 0];
+const WHITE: [u8; 3] = [255, 255, 255];
+
+#[test]
+fn test_image_render_cells() {
+    let mut game_of_life = GameOfLife::new(10, 10);
+    game_of_life.set(2, 3, CellState::Alive);
+
+    let viewport = Viewport::new(((0, 0), (9, 9)), 4.0);
+    let image = render(&game_of_life, &viewport, &Style::default());
+    assert_eq!((image.width, image.height), (40, 40));
+    for y in 0..40 {
+        for x in 0..40 {
+            let alive = x / 4 == 2 && y / 4 == 3;
+            assert_eq!(image.pixel(x, y), if alive { BLACK } else { WHITE });
+        }
+    }
+
+    // The region is drawn from its top-left corner, with its edges in the foreground color.
+    let style = Style {
+        border: true,
+        ..Style::default()
+    };
+    let image = render(&game_of_life, &Viewport::new(((1, 1), (5, 5)), 1.0), &style);
+    assert_eq!((image.width, image.height), (5, 5));
+    assert_eq!(image.pixel(1, 2), BLACK);
+    assert_eq!(image.pixel(2, 2), WHITE);
+    assert_eq!(image.pixel(4, 2), BLACK);
+    assert_eq!(image.pixel(2, 0), BLACK);
+}
+
+#[test]
+fn test_image_render_blocks() {
+    let mut sparse_life = SparseLife::new(Rule::conway()).unwrap();
+    for &(x, y) in &[(0, 0), (1, 0), (2, 0), (3, 0), (4, 0)] {
+        sparse_life.set(x, y, CellState::Alive);
+    }
+
+    // Every pixel shows a block of 4 by 4 cells.
+    let viewport = Viewport::new(((0, 0), (15, 15)), 0.25);
+    assert_eq!(viewport.block(), 4);
+    let mut style = Style::default();
+    let image = render(&sparse_life, &viewport, &style);
+    assert_eq!((image.width, image.height), (4, 4));
+    assert_eq!(image.pixel(0, 0), [191, 191, 191]);
+    assert_eq!(image.pixel(1, 0), [239, 239, 239]);
+    assert_eq!(image.pixel(2, 0), WHITE);
+    assert_eq!(image.pixel(0, 1), WHITE);
+
+    style.shading = Shading::AnyAlive;
+    let image = render(&sparse_life, &viewport, &style);
+    assert_eq!(image.pixel(0, 0), BLACK);
+    assert_eq!(image.pixel(1, 0), BLACK);
+    assert_eq!(image.pixel(2, 0), WHITE);
+}
+
+#[test]
+fn test_image_alive_blocks() {
+    let mut game_of_life = GameOfLife::new(12, 8);
+    for y in 0..6 {
+        for x in 0..10 {
+            game_of_life.set(x, y, CellState::Alive);
+        }
+    }
+
+    // The blocks are aligned to multiples of the block size, and the blocks at the right and
+    // bottom are cut off by the region, so that all of them are fully alive.
+    let blocks = alive_blocks(&game_of_life, ((2, 1), (9, 5)), 4);
+    let positions: Vec<_> = blocks.iter().map(|b| (b.x, b.y)).collect();
+    assert_eq!(
+        positions,
+        vec![(0, 0), (4, 0), (8, 0), (0, 4), (4, 4), (8, 4)]
+    );
+    assert_eq!(
+        blocks[5],
+        Block {
+            x: 8,
+            y: 4,
+            width: 2,
+            height: 2,
+            population: 4,
+        }
+    );
+    for block in &blocks {
+        assert_eq!(block.shade(Shading::Density), 1.0);
+    }
+
+    // Moving the region does not move the blocks.
+    let moved = alive_blocks(&game_of_life, ((3, 2), (9, 5)), 4);
+    assert_eq!(moved[0], blocks[0]);
+}
+
+#[test]
+fn test_image_write_ppm() {
+    let mut image = Image::new(3, 2, WHITE);
+    image.fill_rect(2, 1, 5, 5, [1, 2, 3]);
+    image.fill_rect(-4, -4, 5, 5, BLACK);
+
+    let mut bytes = Vec::new();
+    image.write_ppm(&mut bytes).unwrap();
+    let mut expected = b"P6\n3 2\n255\n".to_vec();
+    expected.extend_from_slice(&[0, 0, 0, 255, 255, 255, 255, 255, 255]);
+    expected.extend_from_slice(&[255, 255, 255, 255, 255, 255, 1, 2, 3]);
+    assert_eq!(bytes, expected);
+}
+
+#[test]
+fn test_image_write_png() {
+    let mut image = Image::new(5, 3, WHITE);
+    image.fill_rect(1, 1, 3, 1, [200, 100, 0]);
+
+    let mut bytes = Vec::new();
+    image.write_png(&mut bytes).unwrap();
+
+    let (info, mut reader) = png::Decoder::new(&bytes[..]).read_info().unwrap();
+    assert_eq!((info.width, info.height), (5, 3));
+    let mut buffer = vec![0; info.buffer_size()];
+    reader.next_frame(&mut buffer).unwrap();
+    let pixels: Vec<_> = image
+        .pixels
+        .iter()
+        .flat_map(|p| p.iter().cloned())
+        .collect();
+    assert_eq!(buffer, pixels);
+
+    assert!(image.save("thumbnail.gif").is_err());
+}
@@ -0,0 +1,137 @@
+extern crate game_of_life;
+use game_of_life::automaton::Automaton;
+use game_of_life::board::Board;
+use game_of_life::hashlife::HashLife;
+use game_of_life::isotropic::NEIGHBOURS;
+use game_of_life::rule::Rule;
+use game_of_life::{CellState, GameOfLife};
+
+const LETTERS: [&str; 9] = [
+    "",
+    "ce",
+    "ceaikn",
+    "ceaiknjqry",
+    "ceaiknjqrytwz",
+    "ceaiknjqry",
+    "ceaikn",
+    "ce",
+    "",
+];
+
+/// Fill a board with a reproducible pseudo-random soup.
+fn soup(game_of_life: &mut GameOfLife, seed: u64) {
+    let mut state = seed;
+    for y in 0..game_of_life.height {
+        for x in 0..game_of_life.width {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            game_of_life.board.set(x, y, (state >> 61 < 3).into());
+        }
+    }
+}
+
+/// Update a board on a plane one cell at a time.
+fn naive_update(game_of_life: &GameOfLife) -> Board {
+    let mut next = Board::new(game_of_life.width, game_of_life.height);
+    for y in 0..game_of_life.height as isize {
+        for x in 0..game_of_life.width as isize {
+            let neighbours = NEIGHBOURS
+                .iter()
+                .enumerate()
+                .filter(|&(_, &(dx, dy))| game_of_life.get(x + dx, y + dy) == CellState::Alive)
+                .fold(0, |neighbours, (i, _)| neighbours | 1 << i);
+            let alive = game_of_life.get(x, y) == CellState::Alive;
+            next.set(
+                x as usize,
+                y as usize,
+                game_of_life.rule.next_cell(alive, neighbours).into(),
+            );
+        }
+    }
+
+    next
+}
+
+#[test]
+fn test_isotropic_parse_display() {
+    for rule in &["B2-a/S12", "B3-cnqy/S23-a", "B2ce3/S", "B2ea4t/S1e5-k"] {
+        let parsed: Rule = rule.parse().unwrap();
+        assert!(parsed.isotropic.is_some());
+        assert_eq!(parsed.to_string(), *rule);
+    }
+
+    // Rules that list every letter of a count are outer-totalistic.
+    let rule: Rule = "B3ceaiknjqry/S2ceaikn3".parse().unwrap();
+    assert_eq!(rule, Rule::conway());
+    assert_eq!("B2ac/S".parse::<Rule>().unwrap().to_string(), "B2ca/S");
+    assert_eq!("B2cekn/S".parse::<Rule>().unwrap().to_string(), "B2-ai/S");
+
+    assert!("B1k/S".parse::<Rule>().is_err());
+    assert!("B3-/S23".parse::<Rule>().is_err());
+    assert!("B0c/S".parse::<Rule>().is_err());
+}
+
+#[test]
+fn test_isotropic_letters_partition_arrangements() {
+    for (n, letters) in LETTERS.iter().enumerate() {
+        let mut covered = 0;
+        for letter in letters.chars() {
+            let rule: Rule = format!("B{}{}/S", n, letter).parse().unwrap();
+            let isotropic = rule.isotropic.unwrap();
+            let arrangements: Vec<u8> = (0..=255u8).filter(|&a| isotropic.births(a)).collect();
+
+            assert!(arrangements.iter().all(|a| a.count_ones() as usize == n));
+            assert!([1, 2, 4, 8].contains(&arrangements.len()));
+            covered += arrangements.len();
+        }
+
+        let total = (0..=255u8).filter(|a| a.count_ones() as usize == n).count();
+        if !letters.is_empty() {
+            assert_eq!(covered, total, "letters of {} neighbours", n);
+        }
+    }
+}
+
+#[test]
+fn test_isotropic_update_matches_naive_update() {
+    for rule in &["B2-a/S12", "B3-cnqy/S23-a", "B2ce3ak/S1e2-n4wz", "B03e/S8"] {
+        for &(width, height) in &[(5, 3), (64, 4), (70, 20)] {
+            let mut game_of_life = GameOfLife::new(width, height);
+            game_of_life.with_rule(rule.parse().unwrap());
+            soup(&mut game_of_life, 5);
+
+            for _ in 0..4 {
+                let expected = naive_update(&game_of_life);
+                game_of_life.update();
+                assert_eq!(game_of_life.board, expected, "rule {}", rule);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_isotropic_hashlife_matches_game_of_life() {
+    let rule: Rule = "B3-cnqy/S23-a".parse().unwrap();
+    let size = 128;
+    let mut game_of_life = GameOfLife::new(size, size);
+    game_of_life.with_rule(rule.clone());
+    let mut hashlife = HashLife::new(rule).unwrap();
+    for y in 54..74 {
+        for x in 54..74 {
+            if (x * 7 + y * 13) % 5 < 2 {
+                game_of_life.set(x, y, CellState::Alive);
+                hashlife.set(x, y, CellState::Alive);
+            }
+        }
+    }
+
+    hashlife.step_n(20);
+    game_of_life.step_n(20);
+    assert_eq!(hashlife.population(), game_of_life.population());
+    let mut expected = game_of_life.alive_cells(((0, 0), (127, 127)));
+    let mut cells = hashlife.alive_cells(((0, 0), (127, 127)));
+    expected.sort();
+    cells.sort();
+    assert_eq!(cells, expected);
+}
@@ -0,0 +1,147 @@
+extern crate game_of_life;
+use game_of_life::automaton::Automaton;
+use game_of_life::board::Board;
+use game_of_life::larger_than_life::*;
+use game_of_life::neighbourhood::Neighbourhood;
+use game_of_life::rule::Rule;
+use game_of_life::topology::Topology;
+use game_of_life::GameOfLife;
+
+const BOSCO: &str = "R5,C0,M1,S34..58,B34..45,NM";
+
+/// Fill a board with a reproducible pseudo-random soup.
+fn soup(game_of_life: &mut GameOfLife, seed: u64) {
+    let mut state = seed;
+    for y in 0..game_of_life.height {
+        for x in 0..game_of_life.width {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            game_of_life.board.set(x, y, (state >> 62 < 2).into());
+        }
+    }
+}
+
+/// Update a board one cell at a time, counting every cell of every neighbourhood.
+fn naive_update(game_of_life: &GameOfLife) -> Board {
+    let (width, height) = (game_of_life.width, game_of_life.height);
+    let rule = &game_of_life.rule;
+    let ltl = rule.larger_than_life.unwrap();
+    let range = ltl.range as isize;
+    let mut next = Board::new(width, height);
+    next.set_multi_state(rule.states > 2);
+
+    for y in 0..height as isize {
+        for x in 0..width as isize {
+            let mut neighbours = 0;
+            for dy in -range..range + 1 {
+                for dx in -range..range + 1 {
+                    let inside = match ltl.neighbourhood {
+                        Neighbourhood::Moore => true,
+                        Neighbourhood::VonNeumann => dx.abs() + dy.abs() <= range,
+                        Neighbourhood::Circular => dx * dx + dy * dy <= range * range + range,
+                        Neighbourhood::Hexagonal => (dx - dy).abs() <= range,
+                    };
+                    if !inside || (dx == 0 && dy == 0 && !ltl.middle) {
+                        continue;
+                    }
+
+                    let neighbour = game_of_life.topology.map(x + dx, y + dy, width, height);
+                    if let Some((j, i)) = neighbour {
+                        if u8::from(game_of_life.board.get(j, i)) == 1 {
+                            neighbours += 1;
+                        }
+                    }
+                }
+            }
+
+            let state = u8::from(game_of_life.get(x, y));
+            let next_state = match state {
+                0 if (ltl.birth.0..=ltl.birth.1).contains(&neighbours) => 1,
+                0 => 0,
+                1 if (ltl.survival.0..=ltl.survival.1).contains(&neighbours) => 1,
+                _ if state + 1 < rule.states => state + 1,
+                _ => 0,
+            };
+            next.set(x as usize, y as usize, next_state.into());
+        }
+    }
+
+    next
+}
+
+#[test]
+fn test_larger_than_life_parse() {
+    let rule: Rule = BOSCO.parse().unwrap();
+    assert_eq!(rule.states, 2);
+    assert_eq!(
+        rule.larger_than_life,
+        Some(LargerThanLife {
+            range: 5,
+            middle: true,
+            survival: (34, 58),
+            birth: (34, 45),
+            neighbourhood: Neighbourhood::Moore,
+        })
+    );
+    assert_eq!(rule.to_string(), BOSCO);
+
+    let rule: Rule = "R2,C4,M0,S3..5,B4..4,NC".parse().unwrap();
+    assert_eq!(rule.states, 4);
+    assert_eq!(rule.to_string(), "R2,C4,M0,S3..5,B4..4,NC");
+    assert_eq!(
+        "R2,C0,M0,S3..5,B4..4".parse::<Rule>().unwrap().to_string(),
+        "R2,C0,M0,S3..5,B4..4,NM"
+    );
+
+    assert!("R0,C0,M0,S3..5,B4..4,NM".parse::<Rule>().is_err());
+    assert!("R2,C0,M0,S5..3,B4..4,NM".parse::<Rule>().is_err());
+    assert!("R2,C0,M2,S3..5,B4..4,NM".parse::<Rule>().is_err());
+    assert!("R2,C0,M0,S3..5,NM".parse::<Rule>().is_err());
+    assert!("R2,C0,M0,S3..5,B4..4,NX".parse::<Rule>().is_err());
+}
+
+#[test]
+fn test_larger_than_life_range_1_matches_life() {
+    let mut life = GameOfLife::new(70, 40);
+    soup(&mut life, 3);
+    let mut larger_than_life = GameOfLife::new(70, 40);
+    larger_than_life.with_rule("R1,C0,M0,S2..3,B3..3,NM".parse().unwrap());
+    soup(&mut larger_than_life, 3);
+
+    for _ in 0..10 {
+        life.update();
+        larger_than_life.update();
+        assert_eq!(larger_than_life.board, life.board);
+    }
+}
+
+#[test]
+fn test_larger_than_life_update_matches_naive_update() {
+    let rules = [
+        BOSCO,
+        "R3,C0,M0,S5..12,B6..9,NN",
+        "R4,C3,M1,S10..30,B12..20,NC",
+        "R7,C0,M0,S0..20,B0..3,NM",
+        "R3,C0,M1,S8..17,B9..13,NH",
+    ];
+
+    for rule in &rules {
+        for &topology in &[Topology::Plane, Topology::Torus] {
+            let mut game_of_life = GameOfLife::new(40, 23);
+            game_of_life.with_rule(rule.parse().unwrap());
+            game_of_life.with_topology(topology).unwrap();
+            soup(&mut game_of_life, 11);
+
+            for _ in 0..3 {
+                let expected = naive_update(&game_of_life);
+                game_of_life.update();
+                assert_eq!(
+                    game_of_life.board, expected,
+                    "rule {} on a {}",
+                    rule, topology
+                );
+            }
+        }
+    }
+}
@@ -1,5 +1,6 @@
 extern crate game_of_life;
 use game_of_life::parsers::life_105::*;
+use game_of_life::parsers::Pattern;
 
 #[test]
 fn test_life_105_is_life_105_file() {
@@ -108,3 +109,93 @@ fn test_life_105_incorrect_file() {
 ";
     assert!(parse_life_105_file(&file).is_err());
 }
+
+#[test]
+fn test_life_105_format() {
+    let pattern = Pattern {
+        cells: vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)],
+        name: Some(String::from("Glider")),
+        author: Some(String::from("Richard K. Guy")),
+        rule: Some(String::from("B36/S23")),
+        ..Pattern::default()
+    };
+    assert_eq!(
+        format_life_105_file(&pattern).unwrap(),
+        "#Life 1.05\n#D Name: Glider\n#D Author: Richard K. Guy\n#R 23/36\n#P 0 0\n.*\n..*\n***\n"
+    );
+
+    // Wider patterns are split in several blocks of at most 80 columns.
+    let pattern = Pattern {
+        cells: vec![(-3, 2), (0, 2), (120, 3), (250, 2), (5, 9)],
+        ..Pattern::default()
+    };
+    let formatted = format_life_105_file(&pattern).unwrap();
+    assert!(formatted.contains("#N\n"));
+    assert!(formatted.lines().filter(|l| l.starts_with("#P")).count() > 1);
+    assert!(formatted.lines().all(|l| l.len() <= 80));
+
+    let pattern = Pattern {
+        cells: vec![(0, 0)],
+        states: vec![2],
+        ..Pattern::default()
+    };
+    assert!(format_life_105_file(&pattern).is_err());
+}
+
+#[test]
+fn test_life_105_block_position() {
+    // The line after `#P -19 -10` is the row at y = -10.
+    let file = std::fs::read_to_string("examples/B-52_Bomber_105.life").unwrap();
+    let pattern = parse_life_105_file(&file).unwrap();
+    assert_eq!(pattern.cells.len(), 85);
+    assert_eq!(&pattern.cells[..2], &[(-18, -10), (-17, -10)]);
+    assert_eq!(
+        &pattern.cells[pattern.cells.len() - 2..],
+        &[(17, 10), (18, 10)]
+    );
+    assert_eq!(pattern.cells.iter().map(|&(x, _)| x).min(), Some(-19));
+    assert_eq!(pattern.cells.iter().map(|&(_, y)| y).min(), Some(-10));
+    assert_eq!(pattern.cells.iter().map(|&(_, y)| y).max(), Some(10));
+
+    let pattern = parse_life_105_file(&"#Life 1.05\n#P 3 4\n*\n.*\n#P -1 0\n*\n").unwrap();
+    assert_eq!(pattern.cells, vec![(3, 4), (4, 5), (-1, 0)]);
+}
+
+#[test]
+fn test_life_105_metadata() {
+    let file = std::fs::read_to_string("examples/B-52_Bomber_105.life").unwrap();
+    let pattern = parse_life_105_file(&file).unwrap();
+    assert_eq!(pattern.name, Some(String::from("B-52 bomber")));
+    assert_eq!(pattern.author, Some(String::from("Noam Elkies")));
+    assert_eq!(
+        pattern.description,
+        Some(String::from(
+            "A period 104 double-barrelled glider gun. It uses a B-heptomino and emits one\n\
+             glider every 52 generations.\n\
+             www.conwaylife.com/wiki/index.php?title=B-52_bomber"
+        ))
+    );
+    assert_eq!(pattern.rule, None);
+
+    let pattern = parse_life_105_file(&"#Life 1.05\n#R 23/36\n#P 0 0\n*\n").unwrap();
+    assert_eq!(pattern.rule, Some(String::from("23/36")));
+}
+
+#[test]
+fn test_life_105_round_trip() {
+    let file = std::fs::read_to_string("examples/B-52_Bomber_105.life").unwrap();
+    let mut pattern = parse_life_105_file(&file).unwrap();
+    let mut parsed = parse_life_105_file(&format_life_105_file(&pattern).unwrap()).unwrap();
+    pattern.cells.sort();
+    parsed.cells.sort();
+    assert_eq!(parsed, pattern);
+
+    let mut pattern = Pattern {
+        cells: vec![(-3, 2), (0, 2), (120, 3), (250, 2), (5, 9)],
+        ..Pattern::default()
+    };
+    let mut parsed = parse_life_105_file(&format_life_105_file(&pattern).unwrap()).unwrap();
+    pattern.cells.sort();
+    parsed.cells.sort();
+    assert_eq!(parsed.cells, pattern.cells);
+}
@@ -20,3 +20,41 @@ fn test_life_106_incorrect_file() {
     let file = "#Life 1.06\na b\nc d";
     assert!(parse_life_106_file(&file).is_err());
 }
+
+#[test]
+fn test_life_106_format() {
+    let pattern = parse_life_106_file(&"#Life 1.06\n0 -1\n1 0\n-1 1").unwrap();
+    assert_eq!(
+        format_life_106_file(&pattern).unwrap(),
+        "#Life 1.06\n0 -1\n1 0\n-1 1\n"
+    );
+}
+
+#[test]
+fn test_life_106_round_trip() {
+    for file in &[
+        "examples/glider_106.lif",
+        "examples/gosperglidergun_106.lif",
+        "examples/3enginecordershipgun_106.lif",
+    ] {
+        let mut pattern = parse_life_106_file(&std::fs::read_to_string(file).unwrap()).unwrap();
+        let mut parsed = parse_life_106_file(&format_life_106_file(&pattern).unwrap()).unwrap();
+        pattern.cells.sort();
+        parsed.cells.sort();
+        assert_eq!(parsed, pattern);
+    }
+}
+
+#[test]
+fn test_life_106_metadata() {
+    let file = "#Life 1.06\n#D Name: Glider\n#D Author: Richard K. Guy\n#D The smallest\n#D spaceship.\n1 0\n2 1\n0 2\n1 2\n2 2\n";
+    let pattern = parse_life_106_file(&file).unwrap();
+    assert_eq!(pattern.name, Some(String::from("Glider")));
+    assert_eq!(pattern.author, Some(String::from("Richard K. Guy")));
+    assert_eq!(
+        pattern.description,
+        Some(String::from("The smallest\nspaceship."))
+    );
+    assert_eq!(pattern.cells.len(), 5);
+    assert_eq!(format_life_106_file(&pattern).unwrap(), file);
+}
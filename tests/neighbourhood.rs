@@ -0,0 +1,109 @@
+extern crate game_of_life;
+use game_of_life::automaton::Automaton;
+use game_of_life::board::Board;
+use game_of_life::neighbourhood::Neighbourhood;
+use game_of_life::rule::Rule;
+use game_of_life::sparse::SparseLife;
+use game_of_life::{CellState, GameOfLife};
+
+const HEXAGONAL: [(isize, isize); 6] = [(-1, -1), (0, -1), (-1, 0), (1, 0), (0, 1), (1, 1)];
+const VON_NEUMANN: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+/// Fill a board with a reproducible pseudo-random soup.
+fn soup(game_of_life: &mut GameOfLife, seed: u64) {
+    let mut state = seed;
+    for y in 0..game_of_life.height {
+        for x in 0..game_of_life.width {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            game_of_life.board.set(x, y, (state >> 61 < 3).into());
+        }
+    }
+}
+
+/// Update a board on a plane one cell at a time, using the given neighbours.
+fn naive_update(game_of_life: &GameOfLife, neighbours: &[(isize, isize)]) -> Board {
+    let mut next = Board::new(game_of_life.width, game_of_life.height);
+    for y in 0..game_of_life.height as isize {
+        for x in 0..game_of_life.width as isize {
+            let count = neighbours
+                .iter()
+                .filter(|&&(dx, dy)| game_of_life.get(x + dx, y + dy) == CellState::Alive)
+                .count();
+            let alive = if game_of_life.get(x, y) == CellState::Alive {
+                game_of_life.rule.survival[count]
+            } else {
+                game_of_life.rule.birth[count]
+            };
+            next.set(x as usize, y as usize, alive.into());
+        }
+    }
+
+    next
+}
+
+#[test]
+fn test_neighbourhood_parse_display() {
+    let rule: Rule = "B2/S34H".parse().unwrap();
+    assert_eq!(rule.neighbourhood, Neighbourhood::Hexagonal);
+    assert_eq!(rule.to_string(), "B2/S34H");
+    let rule: Rule = "B1/S013V".parse().unwrap();
+    assert_eq!(rule.neighbourhood, Neighbourhood::VonNeumann);
+    assert_eq!(rule.to_string(), "B1/S013V");
+    assert_eq!("B2/S/C3H".parse::<Rule>().unwrap().to_string(), "B2/S/C3H");
+
+    assert!("B7/S34H".parse::<Rule>().is_err());
+    assert!("B2/S5V".parse::<Rule>().is_err());
+    assert!("B2-a/S12H".parse::<Rule>().is_err());
+
+    assert_eq!(Neighbourhood::Moore.size(1), 8);
+    assert_eq!(Neighbourhood::Hexagonal.size(1), 6);
+    assert_eq!(Neighbourhood::VonNeumann.size(1), 4);
+    assert_eq!(Neighbourhood::Hexagonal.size(2), 18);
+}
+
+#[test]
+fn test_neighbourhood_update_matches_naive_update() {
+    let rules: [(&str, &[(isize, isize)]); 4] = [
+        ("B2/S34H", &HEXAGONAL),
+        ("B245/S3H", &HEXAGONAL),
+        ("B1/S013V", &VON_NEUMANN),
+        ("B13/S24V", &VON_NEUMANN),
+    ];
+
+    for &(rule, neighbours) in &rules {
+        for &(width, height) in &[(5, 3), (64, 4), (70, 20)] {
+            let mut game_of_life = GameOfLife::new(width, height);
+            game_of_life.with_rule(rule.parse().unwrap());
+            soup(&mut game_of_life, 9);
+
+            for _ in 0..4 {
+                let expected = naive_update(&game_of_life, neighbours);
+                game_of_life.update();
+                assert_eq!(game_of_life.board, expected, "rule {}", rule);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_neighbourhood_sparse_matches_game_of_life() {
+    let rule: Rule = "B2/S34H".parse().unwrap();
+    let mut game_of_life = GameOfLife::new(200, 200);
+    game_of_life.with_rule(rule.clone());
+    let mut sparse_life = SparseLife::new(rule).unwrap();
+    for &(x, y) in &[(100, 100), (101, 100), (100, 101), (103, 99)] {
+        game_of_life.set(x, y, CellState::Alive);
+        sparse_life.set(x, y, CellState::Alive);
+    }
+
+    game_of_life.step_n(30);
+    sparse_life.step_n(30);
+    let mut expected = game_of_life.alive_cells(((0, 0), (199, 199)));
+    let mut cells = sparse_life.alive_cells(((0, 0), (199, 199)));
+    expected.sort();
+    cells.sort();
+    assert!(!expected.is_empty());
+    assert_eq!(cells, expected);
+}
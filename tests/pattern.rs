@@ -0,0 +1,120 @@
+extern crate game_of_life;
+use game_of_life::automaton::Automaton;
+use game_of_life::parsers::Pattern;
+use game_of_life::rule::Rule;
+use game_of_life::{CellState, GameOfLife};
+
+/// An R-pentomino, which has no symmetries.
+fn r_pentomino() -> Pattern {
+    Pattern {
+        cells: vec![(1, 0), (2, 0), (0, 1), (1, 1), (1, 2)],
+        width: Some(3),
+        height: Some(3),
+        ..Pattern::default()
+    }
+}
+
+fn sorted(mut cells: Vec<(isize, isize)>) -> Vec<(isize, isize)> {
+    cells.sort();
+    cells
+}
+
+#[test]
+fn test_pattern_from_automaton_and_paste() {
+    let mut game_of_life = GameOfLife::new(20, 20);
+    r_pentomino().paste(&mut game_of_life, (5, 7));
+    assert_eq!(game_of_life.population(), 5);
+    assert_eq!(game_of_life.get(6, 7), CellState::Alive);
+
+    let pattern = Pattern::from_automaton(&game_of_life, ((4, 6), (8, 10)));
+    assert_eq!(pattern.size(), (5, 5));
+    assert_eq!(
+        sorted(pattern.cells),
+        vec![(1, 2), (2, 1), (2, 2), (2, 3), (3, 1)]
+    );
+    assert!(pattern.states.is_empty());
+}
+
+#[test]
+fn test_pattern_rotate_and_flip() {
+    let mut pattern = r_pentomino();
+    pattern.rotate_clockwise();
+    assert_eq!(
+        sorted(pattern.cells.clone()),
+        vec![(0, 1), (1, 0), (1, 1), (2, 1), (2, 2)]
+    );
+
+    // Rotating four times gives back the same pattern.
+    for _ in 0..3 {
+        pattern.rotate_clockwise();
+    }
+    assert_eq!(sorted(pattern.cells.clone()), sorted(r_pentomino().cells));
+
+    pattern.flip_horizontally();
+    assert_eq!(
+        sorted(pattern.cells.clone()),
+        vec![(0, 0), (1, 0), (1, 1), (1, 2), (2, 1)]
+    );
+    pattern.flip_vertically();
+    pattern.flip_horizontally();
+    pattern.flip_vertically();
+    assert_eq!(sorted(pattern.cells), sorted(r_pentomino().cells));
+}
+
+#[test]
+fn test_pattern_rotate_rectangle() {
+    let mut pattern = Pattern {
+        cells: vec![(0, 0), (3, 1)],
+        width: Some(4),
+        height: Some(2),
+        ..Pattern::default()
+    };
+    pattern.rotate_clockwise();
+    assert_eq!(pattern.size(), (2, 4));
+    assert_eq!(sorted(pattern.cells), vec![(0, 3), (1, 0)]);
+}
+
+#[test]
+fn test_pattern_multi_state() {
+    let mut game_of_life = GameOfLife::new(10, 10);
+    game_of_life.with_rule(Rule::brians_brain());
+    game_of_life.set(2, 2, CellState::Alive);
+    game_of_life.set(3, 2, CellState::Dying(1));
+
+    let pattern = Pattern::from_automaton(&game_of_life, ((2, 2), (3, 2)));
+    assert_eq!(pattern.states, vec![1, 2]);
+    assert_eq!(pattern.state(1), CellState::Dying(1));
+
+    let mut copy = GameOfLife::new(10, 10);
+    copy.with_rule(Rule::brians_brain());
+    pattern.paste(&mut copy, (5, 5));
+    assert_eq!(copy.get(6, 5), CellState::Dying(1));
+}
+
+#[test]
+fn test_pattern_to_file() {
+    let pattern = Pattern {
+        name: Some(String::from("R-pentomino")),
+        author: Some(String::from("John Conway")),
+        description: Some(String::from(
+            "A methuselah.\nIt stabilises after 1103 generations.",
+        )),
+        ..r_pentomino()
+    };
+
+    for extension in &["rle", "cells", "life", "lif"] {
+        let path = std::env::temp_dir().join(format!("r_pentomino_to_file.{}", extension));
+        let path = path.to_str().unwrap();
+        pattern.to_file(path).unwrap();
+        let written = Pattern::from_file(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(sorted(written.cells), sorted(pattern.cells.clone()));
+        assert_eq!(written.name, pattern.name);
+        assert_eq!(written.author, pattern.author);
+        assert_eq!(written.description, pattern.description);
+    }
+
+    assert!(pattern.to_file("r_pentomino.txt").is_err());
+    assert!(Pattern::from_file("README.md").is_err());
+}
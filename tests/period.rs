@@ -0,0 +1,52 @@
+extern crate game_of_life;
+use game_of_life::automaton::Automaton;
+use game_of_life::period::Periods;
+use game_of_life::topology::Topology;
+use game_of_life::{CellState, GameOfLife};
+
+/// Step a game of life until its board repeats, and return the generations of the two boards.
+fn repetition(game_of_life: &mut GameOfLife) -> (u64, u64) {
+    let mut periods = Periods::new();
+    loop {
+        if let Some(previous) = periods.check(game_of_life) {
+            return (previous, game_of_life.generation);
+        }
+        game_of_life.update();
+    }
+}
+
+#[test]
+fn test_period_still_life() {
+    let mut game_of_life = GameOfLife::new(8, 8);
+    for &(x, y) in &[(2, 2), (3, 2), (2, 3), (3, 3)] {
+        game_of_life.set(x, y, CellState::Alive);
+    }
+
+    assert_eq!(repetition(&mut game_of_life), (0, 1));
+}
+
+#[test]
+fn test_period_blinker() {
+    let mut game_of_life = GameOfLife::new(8, 8);
+    for x in 2..5 {
+        game_of_life.set(x, 3, CellState::Alive);
+    }
+
+    assert_eq!(repetition(&mut game_of_life), (0, 2));
+}
+
+#[test]
+fn test_period_glider_on_torus() {
+    // The glider moves one cell diagonally every 4 generations, so it is back after crossing the
+    // board, while the boards of all generations in between differ.
+    let mut game_of_life = GameOfLife::new(8, 8);
+    game_of_life.with_topology(Topology::Torus).unwrap();
+    for &(x, y) in &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+        game_of_life.set(x, y, CellState::Alive);
+    }
+    for _ in 0..3 {
+        game_of_life.update();
+    }
+
+    assert_eq!(repetition(&mut game_of_life), (3, 35));
+}
@@ -18,3 +18,26 @@ fn test_plaintext_incorrect_file() {
     let file = "!Name: My name\n.O\n..Owrong characters\nOOO";
     assert!(parse_plaintext_file(&file).is_err())
 }
+
+#[test]
+fn test_plaintext_format() {
+    let pattern = parse_plaintext_file(&"!Name: Glider\n.O\n..O\nOOO\n\n.O").unwrap();
+    assert_eq!(
+        format_plaintext_file(&pattern).unwrap(),
+        "!Name: Glider\n.O\n..O\nOOO\n\n.O\n"
+    );
+
+    let mut pattern = pattern;
+    pattern.states = vec![1, 1, 1, 1, 2, 1];
+    assert!(format_plaintext_file(&pattern).is_err());
+}
+
+#[test]
+fn test_plaintext_round_trip() {
+    let pattern =
+        parse_plaintext_file(&std::fs::read_to_string("examples/lidka.cells").unwrap()).unwrap();
+    assert!(pattern.author.is_some() || pattern.description.is_some());
+
+    let formatted = format_plaintext_file(&pattern).unwrap();
+    assert_eq!(parse_plaintext_file(&formatted), Ok(pattern));
+}
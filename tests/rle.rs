@@ -1,5 +1,6 @@
 extern crate game_of_life;
 use game_of_life::parsers::rle::*;
+use game_of_life::parsers::Pattern;
 
 #[test]
 fn test_rle_correct_file() {
@@ -26,3 +27,91 @@ x = 13, y = 13, rule = B3/S23
 o4bo$o4bobo4bo$o4bobo4bo2$2b3o3b3o!";
     assert!(parse_rle_file(&input).is_err())
 }
+
+#[test]
+fn test_rle_header() {
+    let input = "#N Glider
+x = 3, y = 3, rule = B36/S23
+bo$2bo$3o!";
+    let pattern = parse_rle_file(&input).unwrap();
+    assert_eq!(pattern.width, Some(3));
+    assert_eq!(pattern.height, Some(3));
+    assert_eq!(pattern.rule, Some(String::from("B36/S23")));
+
+    let (width, height, rule) = parse_rle_header(&"x = 5, y = 9").unwrap();
+    assert_eq!((width, height, rule), (5, 9, None));
+
+    let (_, _, rule) =
+        parse_rle_header(&"x = 1, y = 1, rule = R5,C0,M1,S34..58,B34..45,NM").unwrap();
+    assert_eq!(rule, Some(String::from("R5,C0,M1,S34..58,B34..45,NM")));
+
+    // The bounded grid after the rule is left out.
+    let (_, _, rule) = parse_rle_header(&"x = 3, y = 3, rule = B3/S23:T20,20").unwrap();
+    assert_eq!(rule, Some(String::from("B3/S23")));
+}
+
+#[test]
+fn test_rle_incorrect_header() {
+    assert!(parse_rle_header(&"x = 3").is_err());
+    assert!(parse_rle_header(&"x = a, y = 3").is_err());
+    assert!(parse_rle_header(&"x = 3, y = 3, z = 3").is_err());
+    assert!(parse_rle_header(&"bo$2bo$3o!").is_err());
+}
+
+#[test]
+fn test_rle_cells_outside_header_bounds() {
+    let input = "x = 2, y = 3
+bo$2bo$3o!";
+    assert!(parse_rle_file(&input).is_err());
+
+    let input = "x = 3, y = 2
+bo$2bo$3o!";
+    assert!(parse_rle_file(&input).is_err());
+}
+
+#[test]
+fn test_rle_multi_state() {
+    let pattern = parse_rle_file(&"x = 5, y = 2, rule = WireWorld\n.A2C$pAqX!").unwrap();
+    assert_eq!(pattern.cells, vec![(1, 0), (2, 0), (3, 0), (0, 1), (1, 1)]);
+    assert_eq!(pattern.states, vec![1, 3, 3, 25, 72]);
+
+    let pattern = parse_rle_file(&"x = 3, y = 1\nAbo!").unwrap();
+    assert!(pattern.states.is_empty());
+    assert!(parse_rle_file(&"x = 3, y = 1\nkA!").is_err());
+}
+
+#[test]
+fn test_rle_format() {
+    let pattern = Pattern {
+        cells: vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2), (1, 5)],
+        name: Some(String::from("Glider")),
+        rule: Some(String::from("B3/S23")),
+        ..Pattern::default()
+    };
+    assert_eq!(
+        format_rle_file(&pattern),
+        "#N Glider\nx = 3, y = 6, rule = B3/S23\nbo$2bo$3o3$bo!\n"
+    );
+
+    // States are written with letters, and long lines are wrapped between runs.
+    let pattern = parse_rle_file(&"x = 5, y = 2, rule = WireWorld\n.A2C$pAqX!").unwrap();
+    assert_eq!(
+        format_rle_file(&pattern),
+        "x = 5, y = 2, rule = WireWorld\n.A2C$pAqX!\n"
+    );
+    let pattern = Pattern {
+        cells: (0..100).map(|x| (x * 2, 0)).collect(),
+        ..Pattern::default()
+    };
+    let formatted = format_rle_file(&pattern);
+    assert!(formatted.lines().all(|line| line.len() <= 70));
+    assert_eq!(parse_rle_file(&formatted).unwrap().cells, pattern.cells);
+}
+
+#[test]
+fn test_rle_round_trip() {
+    for file in &["examples/Pulsar.rle", "examples/7468M.rle"] {
+        let pattern = parse_rle_file(&std::fs::read_to_string(file).unwrap()).unwrap();
+        assert_eq!(parse_rle_file(&format_rle_file(&pattern)), Ok(pattern));
+    }
+}
@@ -0,0 +1,69 @@
+extern crate game_of_life;
+use game_of_life::rule::*;
+use game_of_life::{CellState, GameOfLife};
+
+#[test]
+fn test_rule_parse_b_s_notation() {
+    let highlife: Rule = "B36/S23".parse().unwrap();
+    assert_eq!(highlife, Rule::new(&[3, 6], &[2, 3]));
+
+    let day_and_night: Rule = "b3678/s34678".parse().unwrap();
+    assert_eq!(day_and_night, Rule::new(&[3, 6, 7, 8], &[3, 4, 6, 7, 8]));
+
+    assert_eq!("S23/B3".parse::<Rule>().unwrap(), Rule::conway());
+}
+
+#[test]
+fn test_rule_parse_s_b_notation() {
+    assert_eq!("23/3".parse::<Rule>().unwrap(), Rule::conway());
+    assert_eq!("/2".parse::<Rule>().unwrap(), Rule::new(&[2], &[]));
+}
+
+#[test]
+fn test_rule_parse_generations() {
+    assert_eq!("B2/S/C3".parse::<Rule>().unwrap(), Rule::brians_brain());
+    assert_eq!("/2/3".parse::<Rule>().unwrap(), Rule::brians_brain());
+    assert_eq!(
+        "345/2/4".parse::<Rule>().unwrap(),
+        Rule::generations(&[2], &[3, 4, 5], 4)
+    );
+    assert_eq!("B3/S23/C2".parse::<Rule>().unwrap(), Rule::conway());
+    assert_eq!(Rule::brians_brain().to_string(), "B2/S/C3");
+    assert!("B2/S/C1".parse::<Rule>().is_err());
+    assert!("B2/S/C256".parse::<Rule>().is_err());
+}
+
+#[test]
+fn test_rule_parse_incorrect_rule() {
+    assert!("B39/S23".parse::<Rule>().is_err());
+    assert!("B3".parse::<Rule>().is_err());
+    assert!("B3/S23/C4/X".parse::<Rule>().is_err());
+    assert!("X3/Y23".parse::<Rule>().is_err());
+}
+
+#[test]
+fn test_rule_display() {
+    assert_eq!(Rule::conway().to_string(), "B3/S23");
+    assert_eq!(
+        "34678/3678".parse::<Rule>().unwrap().to_string(),
+        "B3678/S34678"
+    );
+}
+
+#[test]
+fn test_rule_update() {
+    // A domino dies under Conway's rule, but survives under B/S012345678.
+    let mut game_of_life = GameOfLife::new(5, 5);
+    game_of_life.board.set(2, 2, CellState::Alive);
+    game_of_life.board.set(3, 2, CellState::Alive);
+    game_of_life.update();
+    assert_eq!(game_of_life.board.get(2, 2), CellState::Dead);
+
+    let mut game_of_life = GameOfLife::new(5, 5);
+    game_of_life.with_rule("B/S012345678".parse().unwrap());
+    game_of_life.board.set(2, 2, CellState::Alive);
+    game_of_life.board.set(3, 2, CellState::Alive);
+    game_of_life.update();
+    assert_eq!(game_of_life.board.get(2, 2), CellState::Alive);
+    assert_eq!(game_of_life.board.get(3, 2), CellState::Alive);
+}
@@ -0,0 +1,96 @@
+extern crate game_of_life;
+use game_of_life::automaton::Automaton;
+use game_of_life::neighbourhood::Neighbourhood;
+use game_of_life::rule::Rule;
+use game_of_life::rule_table::RuleTable;
+use game_of_life::GameOfLife;
+
+/// The states of a row of cells.
+fn row(game_of_life: &GameOfLife, y: isize) -> Vec<u8> {
+    (0..game_of_life.width as isize)
+        .map(|x| u8::from(game_of_life.get(x, y)))
+        .collect()
+}
+
+#[test]
+fn test_rule_table_parse() {
+    let table = RuleTable::parse(
+        "@RULE Test
+@TABLE
+n_states:3
+neighborhood:vonNeumann
+symmetries:rotate4
+var a={1,2}
+0,a,0,0,0,1
+# Comments are ignored.
+a,0,0,0,0,0
+@COLORS
+1 255 0 0
+",
+    )
+    .unwrap();
+
+    assert_eq!(table.name, "Test");
+    assert_eq!(table.states, 3);
+    assert_eq!(table.neighbourhood, Neighbourhood::VonNeumann);
+    assert_eq!(table.colors, vec![None, Some([255, 0, 0]), None]);
+
+    // Rotations of the first transition match too.
+    assert_eq!(table.next_state(&[0, 2, 0, 0, 0]), 1);
+    assert_eq!(table.next_state(&[0, 0, 0, 1, 0]), 1);
+    assert_eq!(table.next_state(&[0, 1, 1, 0, 0]), 0);
+    assert_eq!(table.next_state(&[2, 0, 0, 0, 0]), 0);
+    assert_eq!(table.next_state(&[2, 0, 1, 0, 0]), 2);
+
+    assert!(RuleTable::parse("@TABLE\nn_states:2\n0,1,1,1,1,1,1,1,1,1").is_err());
+    assert!(RuleTable::parse("@RULE Test\n@TABLE\nn_states:2\n0,2,0,0,0,0,0,0,0,1").is_err());
+    assert!(RuleTable::parse("@RULE Test\n@TABLE\nn_states:2\n0,1,1").is_err());
+}
+
+#[test]
+fn test_rule_table_wireworld() {
+    let rule: Rule = "WireWorld".parse().unwrap();
+    assert_eq!(rule.states, 4);
+    assert_eq!(rule.to_string(), "WireWorld");
+
+    let mut game_of_life = GameOfLife::new(6, 1);
+    game_of_life.with_rule(rule);
+    for (x, &state) in [2, 1, 3, 3, 3, 3].iter().enumerate() {
+        game_of_life.set(x as isize, 0, state.into());
+    }
+
+    // The electron moves along the wire.
+    game_of_life.update();
+    assert_eq!(row(&game_of_life, 0), vec![3, 2, 1, 3, 3, 3]);
+    game_of_life.update();
+    assert_eq!(row(&game_of_life, 0), vec![3, 3, 2, 1, 3, 3]);
+    assert_eq!(game_of_life.state_color(1), Some([0, 128, 255]));
+}
+
+#[test]
+fn test_rule_table_wireworld_diode() {
+    // Three electron heads next to a conductor block it, so electrons only pass one way.
+    let mut game_of_life = GameOfLife::new(5, 3);
+    game_of_life.with_rule(Rule::wireworld());
+    for &(x, y) in &[(1, 0), (1, 1), (1, 2)] {
+        game_of_life.set(x, y, 1.into());
+    }
+    game_of_life.set(2, 1, 3.into());
+
+    game_of_life.update();
+    assert_eq!(u8::from(game_of_life.get(2, 1)), 3);
+}
+
+#[test]
+fn test_rule_table_multi_state_rle() {
+    let mut game_of_life = GameOfLife::new(8, 8);
+    let filename = std::env::temp_dir().join("game_of_life_test_rule_table.rle");
+    std::fs::write(&filename, "x = 4, y = 2, rule = WireWorld\nBA2C$.C!\n").unwrap();
+    game_of_life
+        .init_with_file(filename.to_str().unwrap())
+        .unwrap();
+
+    assert_eq!(game_of_life.rule, Rule::wireworld());
+    assert_eq!(row(&game_of_life, 4), vec![0, 0, 0, 0, 2, 1, 3, 3]);
+    assert_eq!(row(&game_of_life, 5), vec![0, 0, 0, 0, 0, 3, 0, 0]);
+}
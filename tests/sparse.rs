@@ -0,0 +1,61 @@
+extern crate game_of_life;
+use game_of_life::automaton::Automaton;
+use game_of_life::hashlife::HashLife;
+use game_of_life::parsers::Pattern;
+use game_of_life::rule::Rule;
+use game_of_life::sparse::*;
+use game_of_life::CellState;
+
+#[test]
+fn test_sparse_get_set() {
+    let mut sparse_life = SparseLife::new(Rule::conway()).unwrap();
+    sparse_life.set(-1, -1, CellState::Alive);
+    sparse_life.set(64, 200, CellState::Alive);
+    assert_eq!(sparse_life.get(-1, -1), CellState::Alive);
+    assert_eq!(sparse_life.get(64, 200), CellState::Alive);
+    assert_eq!(sparse_life.get(63, 200), CellState::Dead);
+    assert_eq!(sparse_life.population(), 2);
+    assert_eq!(sparse_life.bounds(), Some(((-1, -1), (64, 200))));
+
+    sparse_life.set(-1, -1, CellState::Dead);
+    assert_eq!(sparse_life.tiles(), 1);
+}
+
+#[test]
+fn test_sparse_glider_crosses_tiles() {
+    let mut sparse_life = SparseLife::new(Rule::conway()).unwrap();
+    for &(x, y) in &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+        sparse_life.set(x, y, CellState::Alive);
+    }
+
+    // A glider moves one cell diagonally every four generations.
+    for _ in 0..4 * 300 {
+        sparse_life.update();
+    }
+    assert_eq!(sparse_life.population(), 5);
+    assert_eq!(sparse_life.bounds(), Some(((300, 300), (302, 302))));
+    assert!(sparse_life.tiles() <= 4);
+}
+
+#[test]
+fn test_sparse_matches_hashlife() {
+    let pattern = Pattern::from_file("./examples/gosperglidergun_106.lif").unwrap();
+    let mut sparse_life = SparseLife::from_pattern(&pattern).unwrap();
+    let mut hashlife = HashLife::from_pattern(&pattern).unwrap();
+
+    for _ in 0..500 {
+        sparse_life.update();
+    }
+    hashlife.step_n(500);
+
+    assert_eq!(sparse_life.generation(), 500);
+    assert_eq!(sparse_life.population(), hashlife.population());
+    assert_eq!(sparse_life.bounds(), hashlife.bounds());
+
+    let ((min_x, min_y), (max_x, max_y)) = sparse_life.bounds().unwrap();
+    for y in min_y..max_y + 1 {
+        for x in min_x..max_x + 1 {
+            assert_eq!(sparse_life.get(x, y), hashlife.get(x, y));
+        }
+    }
+}
@@ -0,0 +1,56 @@
+extern crate game_of_life;
+use game_of_life::topology::*;
+use game_of_life::{CellState, GameOfLife};
+
+#[test]
+fn test_topology_map() {
+    assert_eq!(Topology::Plane.map(2, 3, 5, 5), Some((2, 3)));
+    assert_eq!(Topology::Plane.map(-1, 3, 5, 5), None);
+
+    assert_eq!(Topology::Torus.map(-1, 5, 5, 5), Some((4, 0)));
+    assert_eq!(Topology::KleinBottle.map(1, -1, 5, 5), Some((3, 4)));
+    assert_eq!(Topology::KleinBottle.map(-1, 1, 5, 5), Some((4, 1)));
+    assert_eq!(Topology::CrossSurface.map(-1, 1, 5, 5), Some((4, 3)));
+    assert_eq!(Topology::CrossSurface.map(-1, -1, 5, 5), None);
+    assert_eq!(Topology::Sphere.map(3, -1, 5, 5), Some((0, 3)));
+    assert_eq!(Topology::Sphere.map(5, 1, 5, 5), Some((1, 4)));
+}
+
+#[test]
+fn test_topology_parse() {
+    assert_eq!(
+        "klein-bottle".parse::<Topology>(),
+        Ok(Topology::KleinBottle)
+    );
+    assert_eq!(Topology::CrossSurface.to_string(), "cross-surface");
+    assert!("donut".parse::<Topology>().is_err());
+}
+
+#[test]
+fn test_topology_torus_glider() {
+    // A glider moves one cell diagonally every four generations, so on a torus it returns to its
+    // original position after `4 * width` generations.
+    let mut game_of_life = GameOfLife::new(8, 8);
+    game_of_life.with_topology(Topology::Torus).unwrap();
+    for &(x, y) in &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+        game_of_life.board.set(x, y, CellState::Alive);
+    }
+    let initial = game_of_life.board.clone();
+
+    for _ in 0..4 * 8 {
+        game_of_life.update();
+    }
+    assert_eq!(game_of_life.board, initial);
+}
+
+#[test]
+fn test_topology_sphere_needs_square_board() {
+    let mut game_of_life = GameOfLife::new(8, 5);
+    assert!(game_of_life.with_topology(Topology::Sphere).is_err());
+    assert_eq!(game_of_life.topology, Topology::Plane);
+    assert!(game_of_life.with_topology(Topology::Torus).is_ok());
+
+    let mut game_of_life = GameOfLife::new(6, 6);
+    assert!(game_of_life.with_topology(Topology::Sphere).is_ok());
+    assert_eq!(game_of_life.topology, Topology::Sphere);
+}